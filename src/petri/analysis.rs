@@ -0,0 +1,314 @@
+//! Offline state-space exploration over a [`CpnEngine`].
+//!
+//! Builds the Karp-Miller coverability tree from a net's current marking,
+//! without firing anything through the live engine. This lets a protocol
+//! definition be checked for deadlocks and unbounded places up front,
+//! independent of (or before) a concrete Miri run.
+
+use std::collections::{HashSet, VecDeque};
+
+use rustc_data_structures::fx::FxHashMap;
+
+use super::cpn::{ArcSpec, ArcTokenPattern, CpnEngine, Marking, PlaceId, Token, Transition, TransitionId};
+
+/// A safety bound on the number of coverability-tree nodes explored, in
+/// case a net's structure defeats omega-acceleration in some corner case
+/// and would otherwise loop. Exploration stops early (report is still
+/// returned, just possibly incomplete) rather than hanging.
+pub const DEFAULT_EXPLORATION_LIMIT: usize = 100_000;
+
+/// Report produced by [`explore`].
+#[derive(Debug, Clone)]
+pub struct CoverabilityReport {
+    /// Number of distinct (covering-deduplicated) markings visited.
+    pub reachable_count: usize,
+    /// Markings in which no transition was enabled under any binding.
+    pub deadlocks: Vec<Marking>,
+    /// Place/token pairs found to grow unboundedly (reached omega).
+    pub unbounded: Vec<(PlaceId, Token)>,
+    /// Whether exploration stopped early due to [`DEFAULT_EXPLORATION_LIMIT`].
+    pub truncated: bool,
+}
+
+/// Enumerate the bindings that could possibly enable `transition` under
+/// `marking`: the Cartesian product, per `Variable` arc pattern, of the
+/// tokens actually present in that arc's pre-place. A variable bound by
+/// more than one pre-arc is constrained to the tokens consistent with all
+/// of them.
+fn candidate_bindings(transition: &Transition, marking: &Marking) -> Vec<FxHashMap<String, Token>> {
+    let mut bindings: Vec<FxHashMap<String, Token>> = vec![FxHashMap::default()];
+
+    for arc in &transition.pre {
+        let ArcTokenPattern::Variable(var) = &arc.token else {
+            continue;
+        };
+        let Some(place) = marking.get(&arc.place) else {
+            return Vec::new();
+        };
+        let candidates: Vec<Token> = place.iter().map(|(t, _)| t.clone()).collect();
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        let mut next = Vec::new();
+        for binding in &bindings {
+            match binding.get(var) {
+                Some(existing) if candidates.contains(existing) => next.push(binding.clone()),
+                Some(_) => {} // inconsistent with this arc, drop
+                None =>
+                    for token in &candidates {
+                        let mut b = binding.clone();
+                        b.insert(var.clone(), token.clone());
+                        next.push(b);
+                    },
+            }
+        }
+        bindings = next;
+        if bindings.is_empty() {
+            return Vec::new();
+        }
+    }
+
+    bindings
+}
+
+/// Whether `binding` enables `transition` against `marking` — pre-arc
+/// tokens present, inhibitor arcs absent, guard (if any) satisfied. Shares
+/// `Transition::check_enabled` with [`CpnEngine::fire`] rather than
+/// re-implementing the enabling rules, so offline exploration can't drift
+/// from what the live engine actually fires.
+fn is_enabled(transition: &Transition, marking: &Marking, binding: &FxHashMap<String, Token>) -> bool {
+    transition.check_enabled(marking, binding).is_ok()
+}
+
+/// Fire `transition` under `binding` against `marking`, without touching
+/// the live engine. Only called once `is_enabled` has confirmed the
+/// binding is valid, so pre-arc tokens are assumed present.
+fn fire_pure(transition: &Transition, marking: &Marking, binding: &FxHashMap<String, Token>) -> Marking {
+    let mut next = marking.clone();
+    for arc in &transition.pre {
+        let token = Transition::resolve_token(&arc.token, binding).unwrap_or(Token::Unit);
+        next.get_or_insert(&arc.place).remove(&token, 1);
+    }
+    for arc in &transition.post {
+        let token = Transition::resolve_token(&arc.token, binding).unwrap_or(Token::Unit);
+        next.get_or_insert(&arc.place).add(token, 1);
+    }
+    next
+}
+
+/// All (transition id, successor marking) pairs reachable from `marking` in
+/// one step, across every enabled binding of every transition.
+fn enabled_successors(engine: &CpnEngine, marking: &Marking) -> Vec<(TransitionId, Marking)> {
+    let mut out = Vec::new();
+    for transition in engine.transitions.values() {
+        for binding in candidate_bindings(transition, marking) {
+            if is_enabled(transition, marking, &binding) {
+                out.push((transition.id.clone(), fire_pure(transition, marking, &binding)));
+            }
+        }
+    }
+    out
+}
+
+/// The set of transition ids enabled under `marking` for at least one
+/// binding. Shared with `runtime`'s dead-transition/deadlock tracking, so
+/// online and offline exploration agree on what "enabled" means.
+pub fn enabled_transitions(engine: &CpnEngine, marking: &Marking) -> HashSet<TransitionId> {
+    engine
+        .transitions
+        .values()
+        .filter(|t| candidate_bindings(t, marking).iter().any(|b| is_enabled(t, marking, b)))
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Explore the Karp-Miller coverability tree rooted at `engine`'s current
+/// marking. For each freshly generated successor marking that strictly
+/// covers an ancestor on its own root-to-node path, the strictly-growing
+/// place/token counts are omega-accelerated (set to [`OMEGA`]), and a
+/// marking is not expanded further once some already-visited marking
+/// covers it.
+///
+/// [`OMEGA`]: super::cpn::OMEGA
+pub fn explore(engine: &CpnEngine) -> CoverabilityReport {
+    let initial = engine.marking().clone();
+
+    // Each worklist entry is the root-to-node path, so acceleration can walk
+    // ancestors without a separate parent index.
+    let mut worklist: VecDeque<Vec<Marking>> = VecDeque::new();
+    worklist.push_back(vec![initial]);
+
+    let mut visited: Vec<Marking> = Vec::new();
+    // Indexes `visited` by `Marking::hash`, so the overwhelmingly common
+    // case — re-deriving a marking exactly equal to one already seen — is
+    // an O(1) lookup instead of a linear `covers()` scan. A marking that
+    // merely *covers* (rather than equals) an ancestor still falls back to
+    // the full scan below, since covering is a partial order a hash bucket
+    // can't index.
+    let mut visited_by_hash: FxHashMap<u64, Vec<usize>> = FxHashMap::default();
+    let mut deadlocks = Vec::new();
+    let mut unbounded: Vec<(PlaceId, Token)> = Vec::new();
+    let mut truncated = false;
+
+    while let Some(path) = worklist.pop_front() {
+        if visited.len() >= DEFAULT_EXPLORATION_LIMIT {
+            truncated = true;
+            break;
+        }
+
+        let current = path.last().expect("path is never empty").clone();
+        let current_hash = current.hash();
+
+        // Dedup by covering: a marking already subsumed by a visited one
+        // contributes nothing new. Check the exact-hash bucket first (the
+        // common case), only falling back to scanning every visited
+        // marking when that doesn't already settle it.
+        let already_covered = visited_by_hash
+            .get(&current_hash)
+            .is_some_and(|indices| indices.iter().any(|&i| visited[i].covers(&current)))
+            || visited.iter().any(|v| v.covers(&current));
+        if already_covered {
+            continue;
+        }
+        visited_by_hash.entry(current_hash).or_default().push(visited.len());
+        visited.push(current.clone());
+
+        let successors = enabled_successors(engine, &current);
+        if successors.is_empty() {
+            deadlocks.push(current);
+            continue;
+        }
+
+        for (_transition, mut succ) in successors {
+            for ancestor in &path {
+                if succ.covers(ancestor) {
+                    for (place, token) in succ.strictly_greater_than(ancestor) {
+                        succ.get_or_insert(&place).set_omega(token.clone());
+                        if !unbounded.contains(&(place.clone(), token.clone())) {
+                            unbounded.push((place, token));
+                        }
+                    }
+                }
+            }
+            let mut next_path = path.clone();
+            next_path.push(succ);
+            worklist.push_back(next_path);
+        }
+    }
+
+    CoverabilityReport { reachable_count: visited.len(), deadlocks, unbounded, truncated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri::cpn::Severity;
+
+    /// Test 1: a net with no release transition deadlocks once the lock is
+    /// acquired.
+    #[test]
+    fn test_deadlock_detected() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(42), 1);
+        cpn.set_initial_marking(init);
+        cpn.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+
+        let report = explore(&cpn);
+        assert_eq!(report.deadlocks.len(), 1);
+        assert!(report.deadlocks[0].get("free").map_or(true, |m| m.is_empty()));
+        assert_eq!(report.deadlocks[0].get("held").unwrap().count(&Token::Lock(42)), 1);
+        assert!(report.unbounded.is_empty());
+        assert!(!report.truncated);
+    }
+
+    /// Test 2: a place that is fed faster than it is drained is reported as
+    /// unbounded (omega) rather than exhausting the exploration budget.
+    #[test]
+    fn test_unbounded_place_detected() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("counter", Token::Unit, 1);
+        cpn.set_initial_marking(init);
+        cpn.add_transition(Transition::new(
+            "inc",
+            vec![ArcSpec { place: "counter".to_string(), token: ArcTokenPattern::Concrete(Token::Unit) }],
+            vec![
+                ArcSpec { place: "counter".to_string(), token: ArcTokenPattern::Concrete(Token::Unit) },
+                ArcSpec { place: "counter".to_string(), token: ArcTokenPattern::Concrete(Token::Unit) },
+            ],
+        ));
+
+        let report = explore(&cpn);
+        assert!(!report.truncated);
+        assert_eq!(report.unbounded, vec![("counter".to_string(), Token::Unit)]);
+        assert!(report.deadlocks.is_empty());
+    }
+
+    /// Test 3: an inhibitor arc that permanently blocks a transition must
+    /// turn up as a deadlock in offline exploration, exactly as it would
+    /// online. Before `is_enabled` honored `inhibit`, this transition was
+    /// (wrongly) treated as always enabled and the deadlock was missed.
+    #[test]
+    fn test_inhibitor_arc_produces_deadlock() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(42), 1);
+        init.add_token("writer", Token::Tid(1), 1);
+        cpn.set_initial_marking(init);
+        cpn.add_transition(
+            Transition::new(
+                "acquire",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+                vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            )
+            .with_inhibit(vec![ArcSpec {
+                place: "writer".to_string(),
+                token: ArcTokenPattern::Concrete(Token::Tid(1)),
+            }]),
+        );
+
+        let report = explore(&cpn);
+        assert_eq!(report.reachable_count, 1);
+        assert_eq!(report.deadlocks.len(), 1);
+        assert_eq!(report.deadlocks[0].get("free").unwrap().count(&Token::Lock(42)), 1);
+        assert!(report.deadlocks[0].get("held").map_or(true, |m| m.is_empty()));
+    }
+
+    /// Test 4: a two-step cycle that returns to the initial marking exactly
+    /// dedups against it, terminating exploration instead of looping —
+    /// exercises the `Marking::hash`-indexed fast path in `explore`'s
+    /// covering check, not just the linear `covers()` fallback.
+    #[test]
+    fn test_revisited_marking_deduped_via_hash() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("p1", Token::Lock(1), 1);
+        // Declare `p2` up front: firing leaves an emptied place in the
+        // marking, so the cycle only returns to an identical (same-hash)
+        // marking if the initial one already has that entry.
+        init.get_or_insert("p2");
+        cpn.set_initial_marking(init);
+        cpn.add_transition(Transition::new(
+            "t1",
+            vec![ArcSpec { place: "p1".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "p2".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        cpn.add_transition(Transition::new(
+            "t2",
+            vec![ArcSpec { place: "p2".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "p1".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+
+        let report = explore(&cpn);
+        assert!(!report.truncated);
+        assert_eq!(report.reachable_count, 2);
+        assert!(report.deadlocks.is_empty());
+        assert!(report.unbounded.is_empty());
+    }
+}
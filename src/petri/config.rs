@@ -2,6 +2,10 @@
 
 use std::path::PathBuf;
 
+use rustc_data_structures::fx::FxHashMap;
+
+use super::cpn::{Marking, Severity};
+
 /// Configuration for the Petri net monitor.
 #[derive(Debug, Clone)]
 pub struct PetriConfig {
@@ -13,6 +17,31 @@ pub struct PetriConfig {
     pub fail_fast: bool,
     /// If true, print current marking after each event (for debugging).
     pub print_marking_on_each_event: bool,
+    /// Per-event-type severity overrides, keyed by `PetriEvent::event_type_name`.
+    /// Takes precedence over the firing transition's own `severity` when
+    /// present, so e.g. all `AtomicLoad`/`AtomicStore` violations can be
+    /// downgraded to warnings without editing the net definition.
+    pub event_severity: FxHashMap<String, Severity>,
+    /// Unix socket path to an external relay monitor. Takes precedence over
+    /// `relay_fd` if both are set. See `petri::relay`.
+    pub relay_path: Option<PathBuf>,
+    /// Raw fd of an already-connected relay socket (e.g. inherited from a
+    /// parent process), used when `relay_path` is not set.
+    pub relay_fd: Option<i32>,
+    /// Markings considered a successful end state: exempted from deadlock
+    /// reporting in `PetriRuntime::analysis_report`, even though no
+    /// transition is enabled there.
+    pub accepting_markings: Vec<Marking>,
+    /// If true, `PetriRuntime::check_analysis` reports an error when
+    /// `analysis_report` finds any dead transition or deadlock marking.
+    pub fail_on_analysis_issues: bool,
+    /// If true, record every observed marking's enabled/fired transitions
+    /// for `PetriRuntime::analysis_report`, even when `fail_on_analysis_issues`
+    /// is off. `fail_on_analysis_issues` implies this; unset otherwise,
+    /// since the bookkeeping costs a `Marking` clone and a full enabled-set
+    /// scan on every event, for every existing monitor user that never
+    /// calls `analysis_report`.
+    pub collect_coverage: bool,
 }
 
 impl PetriConfig {
@@ -22,6 +51,12 @@ impl PetriConfig {
             log_path: None,
             fail_fast: true,
             print_marking_on_each_event: false,
+            event_severity: FxHashMap::default(),
+            relay_path: None,
+            relay_fd: None,
+            accepting_markings: Vec::new(),
+            fail_on_analysis_issues: false,
+            collect_coverage: false,
         }
     }
 
@@ -39,4 +74,34 @@ impl PetriConfig {
         self.print_marking_on_each_event = v;
         self
     }
+
+    pub fn with_event_severity(mut self, event_type: impl Into<String>, severity: Severity) -> Self {
+        self.event_severity.insert(event_type.into(), severity);
+        self
+    }
+
+    pub fn with_relay_path(mut self, path: PathBuf) -> Self {
+        self.relay_path = Some(path);
+        self
+    }
+
+    pub fn with_relay_fd(mut self, fd: i32) -> Self {
+        self.relay_fd = Some(fd);
+        self
+    }
+
+    pub fn with_accepting_markings(mut self, markings: Vec<Marking>) -> Self {
+        self.accepting_markings = markings;
+        self
+    }
+
+    pub fn with_fail_on_analysis_issues(mut self, v: bool) -> Self {
+        self.fail_on_analysis_issues = v;
+        self
+    }
+
+    pub fn with_collect_coverage(mut self, v: bool) -> Self {
+        self.collect_coverage = v;
+        self
+    }
 }
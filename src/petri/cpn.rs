@@ -20,6 +20,11 @@ pub enum Token {
     Loc(u64),
     Region(u64),
     Unit,
+    /// A token over a user-declared color, for resources the built-in kinds
+    /// don't cover (files, sockets, channels, ...). `color` names the
+    /// declared color (see `runtime`'s `colors` table); `value` is converted
+    /// to that color's declared representation.
+    Colored { color: String, value: ColorValue },
 }
 
 impl fmt::Display for Token {
@@ -30,11 +35,39 @@ impl fmt::Display for Token {
             Token::Loc(l) => write!(f, "Loc({})", l),
             Token::Region(r) => write!(f, "Region({})", r),
             Token::Unit => write!(f, "Unit"),
+            Token::Colored { color, value } => write!(f, "{}({})", color, value),
         }
     }
 }
 
-/// Multiset of tokens (bag of tokens per place).
+/// The converted payload of a [`Token::Colored`]: whichever of these the
+/// color's declared type (`int`/`uint`/`string`/`bool`) maps to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ColorValue {
+    U64(u64),
+    I64(i64),
+    Str(String),
+    Bool(bool),
+}
+
+impl fmt::Display for ColorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorValue::U64(v) => write!(f, "{v}"),
+            ColorValue::I64(v) => write!(f, "{v}"),
+            ColorValue::Str(v) => write!(f, "{v:?}"),
+            ColorValue::Bool(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// Sentinel count representing an unbounded (omega) number of a token,
+/// as produced by Karp-Miller omega-acceleration in `cpn::analysis`.
+/// `OMEGA + n == OMEGA` and `OMEGA` covers any finite count.
+pub const OMEGA: usize = usize::MAX;
+
+/// Multiset of tokens (bag of tokens per place). A count of [`OMEGA`] means
+/// "unboundedly many" rather than a literal `usize::MAX` tokens.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Multiset(FxHashMap<Token, usize>);
 
@@ -44,12 +77,16 @@ impl Multiset {
     }
 
     pub fn add(&mut self, token: Token, count: usize) {
-        *self.0.entry(token).or_insert(0) += count;
+        let entry = self.0.entry(token).or_insert(0);
+        if *entry != OMEGA {
+            *entry = entry.saturating_add(count);
+        }
     }
 
     pub fn remove(&mut self, token: &Token, count: usize) -> bool {
         let entry = self.0.get_mut(token);
         match entry {
+            Some(n) if *n == OMEGA => true,
             Some(n) if *n >= count => {
                 *n -= count;
                 if *n == 0 {
@@ -69,6 +106,16 @@ impl Multiset {
         *self.0.get(token).unwrap_or(&0)
     }
 
+    /// Whether `token` has been marked as unboundedly present ([`OMEGA`]).
+    pub fn is_omega(&self, token: &Token) -> bool {
+        self.0.get(token) == Some(&OMEGA)
+    }
+
+    /// Mark `token` as unboundedly present (omega-acceleration).
+    pub fn set_omega(&mut self, token: Token) {
+        self.0.insert(token, OMEGA);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
@@ -93,12 +140,196 @@ pub enum ArcTokenPattern {
     Variable(String),
 }
 
+/// How seriously a violation of this transition's enabling condition should
+/// be treated. Mirrors how lint frameworks map individual rules to
+/// severities rather than forcing one global abort-or-continue policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Abort the run (subject to `PetriConfig::fail_fast`).
+    Error,
+    /// Log and continue, regardless of `fail_fast`.
+    Warning,
+    /// Log and continue, regardless of `fail_fast`. Handled the same as
+    /// `Warning`; only the severity named in the report differs.
+    Info,
+    /// Suppress entirely: not logged, never aborts.
+    Allow,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Severity::Error
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+            Severity::Allow => write!(f, "allow"),
+        }
+    }
+}
+
+/// Comparison operator for a transition [`Guard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardOp {
+    Eq,
+    Ne,
+}
+
+/// One side of a guard comparison: a bound variable, or a concrete token.
+#[derive(Debug, Clone)]
+pub enum GuardOperand {
+    Variable(String),
+    Concrete(Token),
+}
+
+/// A boolean precondition over bound variables, evaluated against the
+/// binding before a transition fires, e.g. `L != L2` or `tid == owner`.
+/// An operand referring to an unbound variable makes the guard fail closed.
+#[derive(Debug, Clone)]
+pub struct Guard {
+    pub lhs: GuardOperand,
+    pub op: GuardOp,
+    pub rhs: GuardOperand,
+}
+
+impl Guard {
+    pub fn new(lhs: GuardOperand, op: GuardOp, rhs: GuardOperand) -> Self {
+        Self { lhs, op, rhs }
+    }
+
+    fn resolve(operand: &GuardOperand, binding: &FxHashMap<String, Token>) -> Option<Token> {
+        match operand {
+            GuardOperand::Variable(v) => binding.get(v).cloned(),
+            GuardOperand::Concrete(t) => Some(t.clone()),
+        }
+    }
+
+    fn eval(&self, binding: &FxHashMap<String, Token>) -> bool {
+        match (Self::resolve(&self.lhs, binding), Self::resolve(&self.rhs, binding)) {
+            (Some(l), Some(r)) => match self.op {
+                GuardOp::Eq => l == r,
+                GuardOp::Ne => l != r,
+            },
+            _ => false,
+        }
+    }
+}
+
 /// Transition definition with pre and post arcs.
 #[derive(Debug, Clone)]
 pub struct Transition {
     pub id: TransitionId,
     pub pre: Vec<ArcSpec>,
     pub post: Vec<ArcSpec>,
+    /// Inhibitor arcs: the transition is enabled only while the resolved
+    /// token is *absent* from the named place (e.g. "acquire only if no
+    /// writer token exists").
+    pub inhibit: Vec<ArcSpec>,
+    /// Optional binding predicate, checked before consuming any tokens.
+    pub guard: Option<Guard>,
+    /// Severity to report when this transition is found not enabled.
+    /// Defaults to [`Severity::Error`].
+    pub severity: Severity,
+}
+
+impl Transition {
+    pub fn new(id: impl Into<TransitionId>, pre: Vec<ArcSpec>, post: Vec<ArcSpec>) -> Self {
+        Self { id: id.into(), pre, post, inhibit: Vec::new(), guard: None, severity: Severity::default() }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_inhibit(mut self, inhibit: Vec<ArcSpec>) -> Self {
+        self.inhibit = inhibit;
+        self
+    }
+
+    pub fn with_guard(mut self, guard: Guard) -> Self {
+        self.guard = Some(guard);
+        self
+    }
+
+    /// Resolve a token from binding or arc pattern.
+    pub(crate) fn resolve_token(pattern: &ArcTokenPattern, binding: &FxHashMap<String, Token>) -> Option<Token> {
+        match pattern {
+            ArcTokenPattern::Concrete(t) => Some(t.clone()),
+            ArcTokenPattern::Variable(v) => binding.get(v).cloned(),
+        }
+    }
+
+    /// Check whether `binding` enables this transition against `marking`,
+    /// without consuming anything: guard, then inhibitor arcs, then pre-arc
+    /// tokens, in that order — the same checks [`CpnEngine::fire`] runs
+    /// before consuming, and what `analysis::is_enabled` uses so offline
+    /// exploration agrees with the live engine on what "enabled" means.
+    pub(crate) fn check_enabled(&self, marking: &Marking, binding: &FxHashMap<String, Token>) -> Result<(), NotEnabled> {
+        if let Some(guard) = &self.guard {
+            if !guard.eval(binding) {
+                return Err(NotEnabled {
+                    transition: self.id.clone(),
+                    missing: vec![],
+                    reason: NotEnabledReason::GuardFailed,
+                });
+            }
+        }
+
+        for arc in &self.inhibit {
+            if let Some(token) = Self::resolve_token(&arc.token, binding) {
+                if marking.get(&arc.place).is_some_and(|p| p.contains(&token, 1)) {
+                    return Err(NotEnabled {
+                        transition: self.id.clone(),
+                        missing: vec![(arc.place.clone(), token)],
+                        reason: NotEnabledReason::Inhibited,
+                    });
+                }
+            }
+        }
+
+        let mut missing = Vec::new();
+        for arc in &self.pre {
+            let token = Self::resolve_token(&arc.token, binding);
+            let token = match token {
+                Some(t) => t,
+                None => {
+                    missing.push((arc.place.clone(), Token::Unit));
+                    continue;
+                }
+            };
+            let place = marking.get(&arc.place);
+            if place.map_or(true, |p| !p.contains(&token, 1)) {
+                missing.push((arc.place.clone(), token));
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(NotEnabled { transition: self.id.clone(), missing, reason: NotEnabledReason::Missing });
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a transition was found not enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotEnabledReason {
+    /// A pre-arc's token was not present in its place.
+    Missing,
+    /// An inhibitor arc's token *was* present in its place.
+    Inhibited,
+    /// The transition's guard predicate rejected the binding.
+    GuardFailed,
+    /// Not raised by the local engine at all: an external relay monitor
+    /// rejected an event the local net allowed. See `PetriViolation::external_message`.
+    External,
 }
 
 /// Error when a transition cannot fire.
@@ -106,15 +337,33 @@ pub struct Transition {
 pub struct NotEnabled {
     pub transition: TransitionId,
     pub missing: Vec<(PlaceId, Token)>,
+    pub reason: NotEnabledReason,
 }
 
 impl fmt::Display for NotEnabled {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Transition {} not enabled. Missing tokens:", self.transition)?;
-        for (place, token) in &self.missing {
-            write!(f, " {} in place '{}'", token, place)?;
+        match self.reason {
+            NotEnabledReason::GuardFailed => {
+                write!(f, "Transition {} not enabled: guard rejected binding", self.transition)
+            }
+            NotEnabledReason::Inhibited => {
+                write!(f, "Transition {} not enabled: inhibited by", self.transition)?;
+                for (place, token) in &self.missing {
+                    write!(f, " {} present in place '{}'", token, place)?;
+                }
+                Ok(())
+            }
+            NotEnabledReason::Missing => {
+                write!(f, "Transition {} not enabled. Missing tokens:", self.transition)?;
+                for (place, token) in &self.missing {
+                    write!(f, " {} in place '{}'", token, place)?;
+                }
+                Ok(())
+            }
+            NotEnabledReason::External => {
+                write!(f, "Transition {} not enabled: rejected by external monitor", self.transition)
+            }
         }
-        Ok(())
     }
 }
 
@@ -147,6 +396,35 @@ impl Marking {
         self.0.iter()
     }
 
+    /// Whether `self` covers `other`: every (place, token) count in `other`
+    /// is present in `self` with at least that count ([`OMEGA`] covers any
+    /// finite count). Used by Karp-Miller coverability to dedup the
+    /// worklist and to detect when acceleration should kick in.
+    pub fn covers(&self, other: &Marking) -> bool {
+        other.0.iter().all(|(place, multiset)| {
+            let ours = self.0.get(place);
+            multiset.0.iter().all(|(token, count)| {
+                ours.map_or(false, |m| m.contains(token, *count))
+            })
+        })
+    }
+
+    /// Places/tokens where `self` is strictly greater than `ancestor`
+    /// (covers it, and is greater somewhere) — the set to omega-accelerate
+    /// when `self` is found to cover an ancestor marking on its own path.
+    pub fn strictly_greater_than(&self, ancestor: &Marking) -> Vec<(PlaceId, Token)> {
+        let mut out = Vec::new();
+        for (place, multiset) in &self.0 {
+            for (token, &count) in multiset.iter() {
+                let ancestor_count = ancestor.0.get(place).map_or(0, |m| m.count(token));
+                if count != OMEGA && count > ancestor_count {
+                    out.push((place.clone(), token.clone()));
+                }
+            }
+        }
+        out
+    }
+
     /// Compute a hash for coverage tracking.
     pub fn hash(&self) -> u64 {
         use std::collections::hash_map::DefaultHasher;
@@ -191,17 +469,6 @@ impl CpnEngine {
         self.marking = marking;
     }
 
-    /// Resolve a token from binding or arc pattern.
-    fn resolve_token(
-        pattern: &ArcTokenPattern,
-        binding: &FxHashMap<String, Token>,
-    ) -> Option<Token> {
-        match pattern {
-            ArcTokenPattern::Concrete(t) => Some(t.clone()),
-            ArcTokenPattern::Variable(v) => binding.get(v).cloned(),
-        }
-    }
-
     /// Fire a transition with the given binding.
     /// Returns Err(NotEnabled) if pre-conditions are not satisfied.
     pub fn fire(
@@ -209,48 +476,23 @@ impl CpnEngine {
         transition_id: &str,
         binding: &FxHashMap<String, Token>,
     ) -> Result<(), NotEnabled> {
-        let transition = self
-            .transitions
-            .get(transition_id)
-            .ok_or_else(|| NotEnabled {
-                transition: transition_id.to_string(),
-                missing: vec![],
-            })?;
-
-        let mut missing = Vec::new();
-
-        // Check pre-conditions (without consuming).
-        for arc in &transition.pre {
-            let token = Self::resolve_token(&arc.token, binding);
-            let token = match token {
-                Some(t) => t,
-                None => {
-                    missing.push((arc.place.clone(), Token::Unit));
-                    continue;
-                }
-            };
-            let place = self.marking.get(&arc.place);
-            if place.map_or(true, |p| !p.contains(&token, 1)) {
-                missing.push((arc.place.clone(), token));
-            }
-        }
+        let transition = self.transitions.get(transition_id).ok_or_else(|| NotEnabled {
+            transition: transition_id.to_string(),
+            missing: vec![],
+            reason: NotEnabledReason::Missing,
+        })?;
 
-        if !missing.is_empty() {
-            return Err(NotEnabled {
-                transition: transition_id.to_string(),
-                missing,
-            });
-        }
+        transition.check_enabled(&self.marking, binding)?;
 
         // Consume pre tokens.
         for arc in &transition.pre {
-            let token = Self::resolve_token(&arc.token, binding).unwrap_or(Token::Unit);
+            let token = Transition::resolve_token(&arc.token, binding).unwrap_or(Token::Unit);
             self.marking.get_or_insert(&arc.place).remove(&token, 1);
         }
 
         // Produce post tokens.
         for arc in &transition.post {
-            let token = Self::resolve_token(&arc.token, binding);
+            let token = Transition::resolve_token(&arc.token, binding);
             let token = token.unwrap_or(Token::Unit);
             self.marking.get_or_insert(&arc.place).add(token, 1);
         }
@@ -277,6 +519,264 @@ impl Default for CpnEngine {
     }
 }
 
+/// Render a token as the `{"kind": ..., "value": ...}` shape a net
+/// definition's `initial_marking` uses (see `runtime::token_from_kind_value`),
+/// for recording a marking into a trace file or a relay handshake.
+pub(crate) fn token_to_json(token: &Token) -> serde_json::Value {
+    match token {
+        Token::Lock(v) => serde_json::json!({ "kind": "Lock", "value": v }),
+        Token::Loc(v) => serde_json::json!({ "kind": "Loc", "value": v }),
+        Token::Tid(v) => serde_json::json!({ "kind": "Tid", "value": v }),
+        Token::Region(v) => serde_json::json!({ "kind": "Region", "value": v }),
+        Token::Unit => serde_json::json!({ "kind": "Unit", "value": 0 }),
+        Token::Colored { color, value } => {
+            let value = match value {
+                ColorValue::U64(v) => serde_json::json!(v),
+                ColorValue::I64(v) => serde_json::json!(v),
+                ColorValue::Str(v) => serde_json::json!(v),
+                ColorValue::Bool(v) => serde_json::json!(v),
+            };
+            serde_json::json!({ "kind": color, "value": value })
+        }
+    }
+}
+
+/// Render `marking` in the same per-place list-of-tokens shape as a net
+/// definition's `initial_marking`.
+pub(crate) fn marking_to_json(marking: &Marking) -> FxHashMap<PlaceId, Vec<serde_json::Value>> {
+    marking
+        .iter()
+        .map(|(place, multiset)| {
+            let tokens = multiset
+                .iter()
+                .flat_map(|(token, &count)| std::iter::repeat(token_to_json(token)).take(count))
+                .collect();
+            (place.clone(), tokens)
+        })
+        .collect()
+}
+
+/// Render one arc spec as `{"place", "variable"}` or `{"place", "kind",
+/// "value"}` — the same shape a net definition's `ArcTokenDef` parses.
+fn arc_spec_to_json(arc: &ArcSpec) -> serde_json::Value {
+    match &arc.token {
+        ArcTokenPattern::Variable(v) => serde_json::json!({ "place": arc.place, "variable": v }),
+        ArcTokenPattern::Concrete(token) => {
+            let rendered = token_to_json(token);
+            serde_json::json!({ "place": arc.place, "kind": rendered["kind"], "value": rendered["value"] })
+        }
+    }
+}
+
+/// Render one guard operand as the variable name it binds, or (for a
+/// `Concrete` operand, which a net definition's JSON `GuardDef` can't
+/// actually express) its `Display` rendering.
+fn guard_operand_to_json(operand: &GuardOperand) -> String {
+    match operand {
+        GuardOperand::Variable(v) => v.clone(),
+        GuardOperand::Concrete(t) => t.to_string(),
+    }
+}
+
+fn guard_to_json(guard: &Guard) -> serde_json::Value {
+    serde_json::json!({
+        "lhs": guard_operand_to_json(&guard.lhs),
+        "op": match guard.op { GuardOp::Eq => "==", GuardOp::Ne => "!=" },
+        "rhs": guard_operand_to_json(&guard.rhs),
+    })
+}
+
+/// Render one transition's arcs, guard, and severity in the same shape a
+/// net definition's `TransitionDef` parses.
+fn transition_to_json(transition: &Transition) -> serde_json::Value {
+    serde_json::json!({
+        "pre": transition.pre.iter().map(arc_spec_to_json).collect::<Vec<_>>(),
+        "post": transition.post.iter().map(arc_spec_to_json).collect::<Vec<_>>(),
+        "inhibit": transition.inhibit.iter().map(arc_spec_to_json).collect::<Vec<_>>(),
+        "guard": transition.guard.as_ref().map(guard_to_json),
+        "severity": transition.severity.to_string(),
+    })
+}
+
+/// The place ids `engine`'s transitions and marking actually reference,
+/// sorted for a stable rendering (there's no separate place registry to
+/// read this from — places are implicit in arcs and the marking).
+fn referenced_places(engine: &CpnEngine) -> Vec<PlaceId> {
+    let mut places: std::collections::BTreeSet<PlaceId> =
+        engine.marking().iter().map(|(place, _)| place.clone()).collect();
+    for transition in engine.transitions.values() {
+        for arc in transition.pre.iter().chain(&transition.post).chain(&transition.inhibit) {
+            places.insert(arc.place.clone());
+        }
+    }
+    places.into_iter().collect()
+}
+
+/// Render `engine`'s full net definition — places, transitions (with their
+/// arcs/guard/severity), and the current marking — in the same shape a net
+/// definition file uses. Used for the relay handshake, so an external
+/// monitor can reconstruct the net and track its marking independently
+/// instead of just knowing which transition ids exist.
+pub(crate) fn net_definition_to_json(engine: &CpnEngine) -> serde_json::Value {
+    let transitions: FxHashMap<TransitionId, serde_json::Value> =
+        engine.transitions.iter().map(|(id, t)| (id.clone(), transition_to_json(t))).collect();
+    serde_json::json!({
+        "places": referenced_places(engine),
+        "transitions": transitions,
+        "initial_marking": marking_to_json(engine.marking()),
+    })
+}
+
+/// Escape a string for use inside a DOT label (quotes, backslashes, newlines).
+pub(crate) fn escape_dot_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Multiset {
+    /// Render this multiset as a DOT node label body, e.g. `Lock(42) x1\nTid(0) x1`.
+    fn to_label(&self) -> String {
+        let mut tokens: Vec<String> = self
+            .0
+            .iter()
+            .map(|(t, c)| format!("{} x{}", t, format_count(*c)))
+            .collect();
+        tokens.sort();
+        tokens.join("\n")
+    }
+}
+
+/// Render a token count, showing [`OMEGA`] as `ω`.
+fn format_count(count: usize) -> String {
+    if count == OMEGA { "ω".to_string() } else { count.to_string() }
+}
+
+impl Marking {
+    /// Render this marking alone as a DOT graph: one circle node per place,
+    /// labeled with its current token multiset. Useful for a standalone
+    /// snapshot when the transition structure isn't relevant.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_highlight(&[])
+    }
+
+    /// Like [`Marking::to_dot`], but renders the given places in red.
+    pub fn to_dot_with_highlight(&self, highlight_places: &[PlaceId]) -> String {
+        let mut out = String::new();
+        out.push_str("digraph Marking {\n");
+        let mut places: Vec<_> = self.0.keys().collect();
+        places.sort();
+        for place in places {
+            let multiset = &self.0[place];
+            let label = if multiset.is_empty() {
+                escape_dot_label(place)
+            } else {
+                format!("{}\\n[{}]", escape_dot_label(place), escape_dot_label(&multiset.to_label()))
+            };
+            let color = if highlight_places.iter().any(|p| p == place) {
+                ", color=red, fontcolor=red"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  \"{0}\" [shape=circle, label=\"{1}\"{2}];\n",
+                escape_dot_label(place),
+                label,
+                color
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl CpnEngine {
+    /// Render the net structure and current marking as a Graphviz DOT digraph:
+    /// places as circles (labeled with their token multiset), transitions as
+    /// boxes, and pre/post arcs as edges labeled with the arc's token pattern.
+    ///
+    /// Pipe the output to `dot -Tpng` (or similar) to visualize protocol state.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_highlight(&[])
+    }
+
+    /// Like [`CpnEngine::to_dot`], but renders the given places in red —
+    /// used to highlight where tokens were missing when a transition failed
+    /// to fire.
+    pub fn to_dot_with_highlight(&self, highlight_places: &[PlaceId]) -> String {
+        let mut out = String::new();
+        out.push_str("digraph Cpn {\n");
+
+        let mut places: Vec<&PlaceId> = self.marking.0.keys().collect();
+        for t in self.transitions.values() {
+            for arc in t.pre.iter().chain(t.post.iter()) {
+                if !places.contains(&&arc.place) {
+                    places.push(&arc.place);
+                }
+            }
+        }
+        places.sort();
+        for place in places {
+            let multiset = self.marking.get(place);
+            let label = match multiset {
+                Some(m) if !m.is_empty() => {
+                    format!("{}\\n[{}]", escape_dot_label(place), escape_dot_label(&m.to_label()))
+                }
+                _ => escape_dot_label(place),
+            };
+            let color = if highlight_places.iter().any(|p| p == place) {
+                ", color=red, fontcolor=red"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  \"place_{0}\" [shape=circle, label=\"{1}\"{2}];\n",
+                escape_dot_label(place),
+                label,
+                color
+            ));
+        }
+
+        let mut transitions: Vec<&Transition> = self.transitions.values().collect();
+        transitions.sort_by(|a, b| a.id.cmp(&b.id));
+        for t in transitions {
+            out.push_str(&format!(
+                "  \"trans_{0}\" [shape=box, label=\"{1}\"];\n",
+                escape_dot_label(&t.id),
+                escape_dot_label(&t.id)
+            ));
+            for arc in &t.pre {
+                out.push_str(&format!(
+                    "  \"place_{0}\" -> \"trans_{1}\" [label=\"{2}\"];\n",
+                    escape_dot_label(&arc.place),
+                    escape_dot_label(&t.id),
+                    escape_dot_label(&arc_pattern_label(&arc.token))
+                ));
+            }
+            for arc in &t.post {
+                out.push_str(&format!(
+                    "  \"trans_{0}\" -> \"place_{1}\" [label=\"{2}\"];\n",
+                    escape_dot_label(&t.id),
+                    escape_dot_label(&arc.place),
+                    escape_dot_label(&arc_pattern_label(&arc.token))
+                ));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Render an arc token pattern for a DOT edge label: the concrete token's
+/// Display form, or the bound variable name (e.g. `L`).
+fn arc_pattern_label(pattern: &ArcTokenPattern) -> String {
+    match pattern {
+        ArcTokenPattern::Concrete(t) => t.to_string(),
+        ArcTokenPattern::Variable(v) => v.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,30 +791,30 @@ mod tests {
         init.add_token("free", Token::Lock(42), 1);
 
         // Transition acquire: pre free has Lock(L), post held has Lock(L).
-        cpn.add_transition(Transition {
-            id: "acquire".to_string(),
-            pre: vec![ArcSpec {
+        cpn.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec {
                 place: "free".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-            post: vec![ArcSpec {
+            vec![ArcSpec {
                 place: "held".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-        });
+        ));
 
         // Transition release: pre held has Lock(L), post free has Lock(L).
-        cpn.add_transition(Transition {
-            id: "release".to_string(),
-            pre: vec![ArcSpec {
+        cpn.add_transition(Transition::new(
+            "release",
+            vec![ArcSpec {
                 place: "held".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-            post: vec![ArcSpec {
+            vec![ArcSpec {
                 place: "free".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-        });
+        ));
 
         cpn.set_initial_marking(init);
 
@@ -339,17 +839,17 @@ mod tests {
         init.add_token("free", Token::Lock(1), 1);
         init.add_token("free", Token::Lock(2), 1);
 
-        cpn.add_transition(Transition {
-            id: "acquire".to_string(),
-            pre: vec![ArcSpec {
+        cpn.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec {
                 place: "free".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-            post: vec![ArcSpec {
+            vec![ArcSpec {
                 place: "held".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-        });
+        ));
 
         cpn.set_initial_marking(init);
 
@@ -374,17 +874,17 @@ mod tests {
         // Empty initial marking.
         cpn.set_initial_marking(Marking::new());
 
-        cpn.add_transition(Transition {
-            id: "acquire".to_string(),
-            pre: vec![ArcSpec {
+        cpn.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec {
                 place: "free".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-            post: vec![ArcSpec {
+            vec![ArcSpec {
                 place: "held".to_string(),
                 token: ArcTokenPattern::Variable("L".to_string()),
             }],
-        });
+        ));
 
         let mut binding = FxHashMap::default();
         binding.insert("L".to_string(), Token::Lock(42));
@@ -395,4 +895,113 @@ mod tests {
         assert_eq!(err.missing[0].0, "free");
         assert_eq!(err.missing[0].1, Token::Lock(42));
     }
+
+    /// Test 4: DOT export mentions places, transitions, and arc labels.
+    #[test]
+    fn test_to_dot_contains_structure() {
+        let mut cpn = CpnEngine::new();
+
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(42), 1);
+        cpn.set_initial_marking(init);
+
+        cpn.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec {
+                place: "free".to_string(),
+                token: ArcTokenPattern::Variable("L".to_string()),
+            }],
+            vec![ArcSpec {
+                place: "held".to_string(),
+                token: ArcTokenPattern::Variable("L".to_string()),
+            }],
+        ));
+
+        let dot = cpn.to_dot();
+        assert!(dot.starts_with("digraph Cpn {"));
+        assert!(dot.contains("shape=circle"));
+        assert!(dot.contains("shape=box"));
+        assert!(dot.contains("label=\"L\""));
+        assert!(dot.contains("Lock(42) x1"));
+    }
+
+    /// Test 5: highlighted places render in red.
+    #[test]
+    fn test_to_dot_with_highlight() {
+        let mut marking = Marking::new();
+        marking.add_token("free", Token::Lock(1), 1);
+        let dot = marking.to_dot_with_highlight(&["free".to_string()]);
+        assert!(dot.contains("color=red"));
+    }
+
+    /// Test 6: an inhibitor arc blocks firing while its token is present.
+    #[test]
+    fn test_inhibitor_arc_blocks_firing() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(42), 1);
+        init.add_token("writer", Token::Tid(1), 1);
+        cpn.set_initial_marking(init);
+
+        cpn.add_transition(
+            Transition::new(
+                "acquire",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+                vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            )
+            .with_inhibit(vec![ArcSpec {
+                place: "writer".to_string(),
+                token: ArcTokenPattern::Variable("tid".to_string()),
+            }]),
+        );
+
+        let mut binding = FxHashMap::default();
+        binding.insert("L".to_string(), Token::Lock(42));
+        binding.insert("tid".to_string(), Token::Tid(1));
+
+        let err = cpn.fire("acquire", &binding).unwrap_err();
+        assert_eq!(err.reason, NotEnabledReason::Inhibited);
+        // No tokens were consumed: the net is unchanged.
+        assert_eq!(cpn.marking().get("free").unwrap().count(&Token::Lock(42)), 1);
+
+        cpn.marking_mut().get_or_insert("writer").remove(&Token::Tid(1), 1);
+        cpn.fire("acquire", &binding).unwrap();
+        assert!(cpn.marking().get("free").unwrap().is_empty());
+    }
+
+    /// Test 7: a guard rejects a binding where two variables collide.
+    #[test]
+    fn test_guard_rejects_binding() {
+        let mut cpn = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        init.add_token("free", Token::Lock(2), 1);
+        cpn.set_initial_marking(init);
+
+        cpn.add_transition(
+            Transition::new(
+                "swap",
+                vec![
+                    ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) },
+                    ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L2".to_string()) },
+                ],
+                vec![],
+            )
+            .with_guard(Guard::new(
+                GuardOperand::Variable("L".to_string()),
+                GuardOp::Ne,
+                GuardOperand::Variable("L2".to_string()),
+            )),
+        );
+
+        let mut same = FxHashMap::default();
+        same.insert("L".to_string(), Token::Lock(1));
+        same.insert("L2".to_string(), Token::Lock(1));
+        assert_eq!(cpn.fire("swap", &same).unwrap_err().reason, NotEnabledReason::GuardFailed);
+
+        let mut distinct = FxHashMap::default();
+        distinct.insert("L".to_string(), Token::Lock(1));
+        distinct.insert("L2".to_string(), Token::Lock(2));
+        cpn.fire("swap", &distinct).unwrap();
+    }
 }
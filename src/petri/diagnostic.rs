@@ -1,6 +1,6 @@
 //! Diagnostic formatting for Petri net violations.
 
-use super::cpn::{Marking, Token};
+use super::cpn::{Marking, NotEnabledReason, Severity, Token};
 use super::event::PetriEvent;
 use std::fmt;
 
@@ -13,10 +13,19 @@ pub struct PetriViolation {
     pub span: Option<SpanLike>,
     pub missing_tokens: Vec<(String, Token)>,
     pub current_marking: Marking,
+    /// Severity of the transition that was found not enabled. Determines
+    /// whether the monitor aborts or logs and continues.
+    pub severity: Severity,
+    /// Why the transition was not enabled (missing tokens, an inhibitor
+    /// arc's token present, or a failed guard).
+    pub reason: NotEnabledReason,
+    /// Set when `reason` is `NotEnabledReason::External`: the message an
+    /// out-of-process relay monitor sent back for this event.
+    pub external_message: Option<String>,
 }
 
 /// Simplified span-like info (avoids pulling in rustc_span in petri public API).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SpanLike {
     pub file: String,
     pub line: u32,
@@ -29,10 +38,11 @@ impl fmt::Display for SpanLike {
     }
 }
 
-/// Format a violation as a human-readable message.
+/// Format a violation as a human-readable message, prefixed with a header
+/// naming its severity (`error`, `warning`, or `info`).
 pub fn format_violation(v: &PetriViolation) -> String {
     let mut s = String::new();
-    s.push_str("Petri net protocol violation: transition not enabled\n");
+    s.push_str(&format!("Petri net protocol {}: transition not enabled\n", v.severity));
     s.push_str(&format!("  Event: {:?}\n", v.event));
     s.push_str(&format!("  Thread ID: {}\n", v.tid));
     if let Some(oid) = v.object_id {
@@ -41,9 +51,24 @@ pub fn format_violation(v: &PetriViolation) -> String {
     if let Some(ref span) = v.span {
         s.push_str(&format!("  Location: {}\n", span));
     }
-    s.push_str("  Missing tokens:\n");
-    for (place, token) in &v.missing_tokens {
-        s.push_str(&format!("    - {} in place '{}'\n", token, place));
+    match v.reason {
+        NotEnabledReason::GuardFailed => s.push_str("  Guard rejected the binding.\n"),
+        NotEnabledReason::Inhibited => {
+            s.push_str("  Inhibited by:\n");
+            for (place, token) in &v.missing_tokens {
+                s.push_str(&format!("    - {} present in place '{}'\n", token, place));
+            }
+        }
+        NotEnabledReason::Missing => {
+            s.push_str("  Missing tokens:\n");
+            for (place, token) in &v.missing_tokens {
+                s.push_str(&format!("    - {} in place '{}'\n", token, place));
+            }
+        }
+        NotEnabledReason::External => {
+            let message = v.external_message.as_deref().unwrap_or("rejected by external monitor");
+            s.push_str(&format!("  External monitor: {}\n", message));
+        }
     }
     s.push_str("  Current marking (key places):\n");
     for (place, multiset) in v.current_marking.iter() {
@@ -57,3 +82,12 @@ pub fn format_violation(v: &PetriViolation) -> String {
     }
     s
 }
+
+/// Render the marking at the time of a violation as a standalone Graphviz
+/// DOT snapshot, with the places that were missing tokens highlighted in
+/// red. Useful alongside [`format_violation`] for visual debugging, e.g.
+/// `dot -Tpng` the output to a file.
+pub fn violation_to_dot(v: &PetriViolation) -> String {
+    let highlight: Vec<String> = v.missing_tokens.iter().map(|(place, _)| place.clone()).collect();
+    v.current_marking.to_dot_with_highlight(&highlight)
+}
@@ -0,0 +1,376 @@
+//! A small hand-written lexer and parser for a textual alternative to the
+//! JSON net definition format, e.g.:
+//!
+//! ```text
+//! place free { Lock(42) }
+//! place held {}
+//!
+//! transition acquire { free: L -> held: L }
+//! transition release { held: L -> free: L }
+//! ```
+//!
+//! Bare capitalized constructors (`Lock(42)`, `Tid(0)`, `Unit`) become
+//! concrete [`Token`]s; lowercase (or otherwise unrecognized) identifiers in
+//! arc patterns become bound [`ArcTokenPattern::Variable`]s.
+
+use super::cpn::{ArcSpec, ArcTokenPattern, Marking, Token, Transition};
+use super::diagnostic::SpanLike;
+
+/// A DSL lex or parse failure, with the location it occurred at.
+#[derive(Debug, Clone)]
+pub struct DslError {
+    pub message: String,
+    pub span: SpanLike,
+}
+
+impl std::fmt::Display for DslError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+/// The net definition parsed from DSL source.
+#[derive(Debug, Clone)]
+pub struct DslNet {
+    pub initial_marking: Marking,
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Int(u64),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Colon,
+    Comma,
+    Arrow,
+    Eof,
+}
+
+struct Lexer<'a> {
+    file: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    src: &'a str,
+    line: u32,
+    col: u32,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str, file: &'a str) -> Self {
+        Self { file, chars: src.char_indices().peekable(), src, line: 1, col: 1 }
+    }
+
+    fn span(&self) -> SpanLike {
+        SpanLike { file: self.file.to_string(), line: self.line, column: self.col }
+    }
+
+    fn error(&self, message: impl Into<String>) -> DslError {
+        DslError { message: message.into(), span: self.span() }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek_char() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => {
+                    while let Some(c) = self.peek_char() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.bump();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Tok, SpanLike)>, DslError> {
+        let mut out = Vec::new();
+        loop {
+            self.skip_trivia();
+            let start = self.span();
+            let Some(c) = self.peek_char() else {
+                out.push((Tok::Eof, start));
+                break;
+            };
+            let tok = match c {
+                '{' => {
+                    self.bump();
+                    Tok::LBrace
+                }
+                '}' => {
+                    self.bump();
+                    Tok::RBrace
+                }
+                '(' => {
+                    self.bump();
+                    Tok::LParen
+                }
+                ')' => {
+                    self.bump();
+                    Tok::RParen
+                }
+                ':' => {
+                    self.bump();
+                    Tok::Colon
+                }
+                ',' => {
+                    self.bump();
+                    Tok::Comma
+                }
+                '-' => {
+                    self.bump();
+                    match self.peek_char() {
+                        Some('>') => {
+                            self.bump();
+                            Tok::Arrow
+                        }
+                        _ => return Err(self.error("expected '>' to complete '->'")),
+                    }
+                }
+                c if c.is_ascii_digit() => {
+                    let mut n = String::new();
+                    while let Some(c) = self.peek_char() {
+                        if c.is_ascii_digit() {
+                            n.push(c);
+                            self.bump();
+                        } else {
+                            break;
+                        }
+                    }
+                    Tok::Int(n.parse().map_err(|_| self.error(format!("invalid integer literal '{n}'")))?)
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut s = String::new();
+                    while let Some(c) = self.peek_char() {
+                        if c.is_alphanumeric() || c == '_' {
+                            s.push(c);
+                            self.bump();
+                        } else {
+                            break;
+                        }
+                    }
+                    Tok::Ident(s)
+                }
+                other => return Err(self.error(format!("unexpected character '{other}'"))),
+            };
+            out.push((tok, start));
+        }
+        let _ = self.src;
+        Ok(out)
+    }
+}
+
+struct Parser<'a> {
+    file: &'a str,
+    tokens: Vec<(Tok, SpanLike)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.pos].0
+    }
+
+    fn span(&self) -> SpanLike {
+        self.tokens[self.pos].1.clone()
+    }
+
+    fn error(&self, message: impl Into<String>) -> DslError {
+        DslError { message: message.into(), span: self.span() }
+    }
+
+    fn bump(&mut self) -> Tok {
+        let tok = self.tokens[self.pos].0.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn expect_ident(&mut self) -> Result<String, DslError> {
+        match self.bump() {
+            Tok::Ident(s) => Ok(s),
+            other => Err(self.error(format!("expected identifier, found {:?}", other))),
+        }
+    }
+
+    fn expect(&mut self, want: &Tok) -> Result<(), DslError> {
+        if self.peek() == want {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {:?}, found {:?}", want, self.peek())))
+        }
+    }
+
+    /// Parse a single token constructor/variable, e.g. `Lock(42)`, `Unit`, or `L`.
+    fn parse_token_pattern(&mut self) -> Result<ArcTokenPattern, DslError> {
+        let name = self.expect_ident()?;
+        let is_constructor =
+            matches!(name.as_str(), "Lock" | "Loc" | "Tid" | "Region" | "Unit") || *self.peek() == Tok::LParen;
+        if !is_constructor {
+            return Ok(ArcTokenPattern::Variable(name));
+        }
+
+        let value = if *self.peek() == Tok::LParen {
+            self.bump();
+            let v = match self.bump() {
+                Tok::Int(n) => n,
+                other => return Err(self.error(format!("expected integer literal, found {:?}", other))),
+            };
+            self.expect(&Tok::RParen)?;
+            Some(v)
+        } else {
+            None
+        };
+
+        let token = match (name.as_str(), value) {
+            ("Lock", Some(v)) => Token::Lock(v),
+            ("Loc", Some(v)) => Token::Loc(v),
+            ("Tid", Some(v)) => Token::Tid(v as u32),
+            ("Region", Some(v)) => Token::Region(v),
+            ("Unit", None) => Token::Unit,
+            (other, _) => return Err(self.error(format!("unknown token constructor '{other}'"))),
+        };
+        Ok(ArcTokenPattern::Concrete(token))
+    }
+
+    fn parse_concrete_token(&mut self) -> Result<Token, DslError> {
+        match self.parse_token_pattern()? {
+            ArcTokenPattern::Concrete(t) => Ok(t),
+            ArcTokenPattern::Variable(v) =>
+                Err(self.error(format!("expected a concrete token, found variable '{v}'"))),
+        }
+    }
+
+    fn parse_place_decl(&mut self, marking: &mut Marking) -> Result<(), DslError> {
+        let place = self.expect_ident()?;
+        marking.get_or_insert(&place);
+        self.expect(&Tok::LBrace)?;
+        while *self.peek() != Tok::RBrace {
+            let token = self.parse_concrete_token()?;
+            marking.get_or_insert(&place).add(token, 1);
+            if *self.peek() == Tok::Comma {
+                self.bump();
+            }
+        }
+        self.expect(&Tok::RBrace)?;
+        Ok(())
+    }
+
+    fn parse_arc_list(&mut self) -> Result<Vec<ArcSpec>, DslError> {
+        let mut arcs = Vec::new();
+        loop {
+            let place = self.expect_ident()?;
+            self.expect(&Tok::Colon)?;
+            let token = self.parse_token_pattern()?;
+            arcs.push(ArcSpec { place, token });
+            if *self.peek() == Tok::Comma {
+                self.bump();
+                continue;
+            }
+            break;
+        }
+        Ok(arcs)
+    }
+
+    fn parse_transition_decl(&mut self) -> Result<Transition, DslError> {
+        let id = self.expect_ident()?;
+        self.expect(&Tok::LBrace)?;
+        let pre = self.parse_arc_list()?;
+        self.expect(&Tok::Arrow)?;
+        let post = self.parse_arc_list()?;
+        self.expect(&Tok::RBrace)?;
+        Ok(Transition::new(id, pre, post))
+    }
+
+    fn parse_net(&mut self) -> Result<DslNet, DslError> {
+        let mut initial_marking = Marking::new();
+        let mut transitions = Vec::new();
+        loop {
+            match self.peek() {
+                Tok::Eof => break,
+                Tok::Ident(kw) if kw == "place" => {
+                    self.bump();
+                    self.parse_place_decl(&mut initial_marking)?;
+                }
+                Tok::Ident(kw) if kw == "transition" => {
+                    self.bump();
+                    transitions.push(self.parse_transition_decl()?);
+                }
+                other => return Err(self.error(format!("expected 'place' or 'transition', found {:?}", other))),
+            }
+        }
+        Ok(DslNet { initial_marking, transitions })
+    }
+}
+
+/// Parse DSL source into a [`DslNet`]. `file` is used only to annotate error
+/// spans (it need not be a real path).
+pub fn parse(source: &str, file: &str) -> Result<DslNet, DslError> {
+    let tokens = Lexer::new(source, file).tokenize()?;
+    Parser { file, tokens, pos: 0 }.parse_net()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test 1: the mutex example from the module docs round-trips into a
+    /// marking and two transitions.
+    #[test]
+    fn test_parse_mutex_net() {
+        let src = "
+            place free { Lock(42) }
+            place held {}
+
+            transition acquire { free: L -> held: L }
+            transition release { held: L -> free: L }
+        ";
+        let net = parse(src, "<test>").unwrap();
+        assert_eq!(net.initial_marking.get("free").unwrap().count(&Token::Lock(42)), 1);
+        assert!(net.initial_marking.get("held").unwrap().is_empty());
+        assert_eq!(net.transitions.len(), 2);
+        let acquire = net.transitions.iter().find(|t| t.id == "acquire").unwrap();
+        assert_eq!(acquire.pre.len(), 1);
+        assert_eq!(acquire.pre[0].place, "free");
+        assert!(matches!(acquire.pre[0].token, ArcTokenPattern::Variable(ref v) if v == "L"));
+    }
+
+    /// Test 2: unknown token constructors are rejected with a located error.
+    #[test]
+    fn test_unknown_constructor_errors() {
+        let src = "place free { Bogus(1) }";
+        let err = parse(src, "<test>").unwrap_err();
+        assert!(err.message.contains("unknown token constructor"));
+    }
+
+    /// Test 3: a missing arrow between pre/post arcs is a parse error.
+    #[test]
+    fn test_missing_arrow_errors() {
+        let src = "transition acquire { free: L held: L }";
+        assert!(parse(src, "<test>").is_err());
+    }
+}
@@ -27,9 +27,14 @@ impl<'tcx, T: crate::MiriInterpCxExt<'tcx>> PetriEvalContextExt<'tcx> for T {
         // };
         match runtime.on_event(event, span) {
             Ok(()) => crate::interp_ok(()),
+            // `Allow` is suppressed entirely: not logged, never aborts.
+            // `Warning`/`Info` are always logged and never abort, regardless
+            // of `fail_fast`. Only `Error` can abort, and only if `fail_fast`
+            // is also set.
+            Err(v) if v.severity == crate::petri::Severity::Allow => crate::interp_ok(()),
             Err(v) => {
                 let msg = crate::petri::PetriRuntime::format_violation(&v);
-                if runtime.fail_fast() {
+                if runtime.violation_aborts(&v) {
                     crate::throw_ub_format!("{}", msg);
                 } else {
                     eprintln!("[Petri] {}", msg);
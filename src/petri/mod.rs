@@ -4,15 +4,25 @@
 //! (lock/unlock, atomic ops, thread spawn/join, etc.) to a Colored Petri Net
 //! and detects protocol violations when a transition is not enabled.
 
+pub mod analysis;
 pub mod config;
 pub mod cpn;
 pub mod diagnostic;
+pub mod dsl;
 pub mod event;
 pub mod hooks;
+pub mod relay;
+pub mod replay;
 pub mod runtime;
 
+pub use self::analysis::{explore, CoverabilityReport};
 pub use self::config::PetriConfig;
-pub use self::cpn::{CpnEngine, Marking, NotEnabled, Token};
-pub use self::diagnostic::{format_violation, PetriViolation, SpanLike};
+pub use self::cpn::{
+    ColorValue, CpnEngine, Guard, GuardOp, GuardOperand, Marking, NotEnabled, NotEnabledReason, Severity, Token,
+};
+pub use self::dsl::{DslError, DslNet};
+pub use self::diagnostic::{format_violation, violation_to_dot, PetriViolation, SpanLike};
 pub use self::event::PetriEvent;
-pub use self::runtime::PetriRuntime;
+pub use self::relay::{RelayClient, RelayVerdict};
+pub use self::replay::{replay_trace, ReplayViolation};
+pub use self::runtime::{AnalysisReport, PetriRuntime, ReplayOutcome};
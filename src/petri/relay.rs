@@ -0,0 +1,228 @@
+//! External-monitor relay mode: forward fired events to an out-of-process
+//! analyzer over a line-delimited JSON protocol, and surface any violations
+//! it reports back through the normal diagnostic path.
+//!
+//! The net is still loaded and fired in-process as usual; the relay is an
+//! additional channel an external monitor can use to veto an event the
+//! local net allowed, e.g. because it's running a heavier analysis (full
+//! state-space exploration, cross-process invariants) that isn't practical
+//! to run inline with the interpreter. Configured via `PetriConfig::relay_path`
+//! / `relay_fd`.
+
+use std::io::{BufRead, BufReader, Read, Write};
+#[cfg(unix)]
+use std::os::unix::io::FromRawFd;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use super::config::PetriConfig;
+use super::cpn::{net_definition_to_json, CpnEngine};
+use super::diagnostic::SpanLike;
+use super::event::PetriEvent;
+
+/// Verdict read back from the external monitor for one forwarded event.
+#[derive(Debug)]
+pub enum RelayVerdict {
+    Ok,
+    Violation(String),
+}
+
+/// A connected relay: a line-delimited JSON channel to an external monitor.
+pub struct RelayClient {
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+    next_seq: u64,
+}
+
+impl RelayClient {
+    /// Connect per `config.relay_path`/`config.relay_fd` (path takes
+    /// precedence), send the handshake, and return `None` if neither is
+    /// configured or the connection fails.
+    #[cfg(unix)]
+    pub fn connect(config: &PetriConfig, engine: &CpnEngine) -> Option<Self> {
+        let stream = if let Some(path) = &config.relay_path {
+            UnixStream::connect(path).ok()?
+        } else if let Some(fd) = config.relay_fd {
+            // SAFETY: the caller configured `relay_fd` as an already-open,
+            // already-connected socket fd it owns the lifetime of.
+            unsafe { UnixStream::from_raw_fd(fd) }
+        } else {
+            return None;
+        };
+        let read_half = stream.try_clone().ok()?;
+
+        let mut client = Self {
+            reader: BufReader::new(Box::new(read_half)),
+            writer: Box::new(stream),
+            next_seq: 0,
+        };
+        client.handshake(engine).ok()?;
+        Some(client)
+    }
+
+    #[cfg(not(unix))]
+    pub fn connect(_config: &PetriConfig, _engine: &CpnEngine) -> Option<Self> {
+        None
+    }
+
+    /// Send the net's full definition once, up front: places, transitions
+    /// with their arcs/guard/severity, and the initial marking (see
+    /// `cpn::net_definition_to_json`). This lets the external side maintain
+    /// its own copy of the marking and track it independently, rather than
+    /// just confirming which transition ids exist.
+    fn handshake(&mut self, engine: &CpnEngine) -> Result<(), String> {
+        self.write_line(&serde_json::json!({ "handshake": net_definition_to_json(engine) }))
+    }
+
+    /// Forward one already-fired event, framed as `{"seq", "event", "span",
+    /// "marking_hash"}`,
+    /// and block for the matching `{"seq", "verdict"}` reply.
+    pub fn send_event(
+        &mut self,
+        event: &PetriEvent,
+        span: Option<&SpanLike>,
+        marking_hash: u64,
+    ) -> Result<RelayVerdict, String> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.write_line(&serde_json::json!({
+            "seq": seq,
+            "event": event,
+            "span": span,
+            "marking_hash": marking_hash,
+        }))?;
+
+        let mut line = String::new();
+        self.reader.read_line(&mut line).map_err(|e| format!("Failed to read relay verdict: {}", e))?;
+        let reply: serde_json::Value =
+            serde_json::from_str(line.trim()).map_err(|e| format!("Failed to parse relay verdict: {}", e))?;
+
+        if reply.get("seq").and_then(|v| v.as_u64()) != Some(seq) {
+            return Err(format!("Relay verdict out of sequence: expected seq {}", seq));
+        }
+
+        match reply.get("verdict") {
+            Some(serde_json::Value::String(s)) if s == "ok" => Ok(RelayVerdict::Ok),
+            Some(serde_json::Value::Object(obj)) => {
+                let message = obj
+                    .get("violation")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("external monitor reported a violation")
+                    .to_string();
+                Ok(RelayVerdict::Violation(message))
+            }
+            other => Err(format!("Unrecognized relay verdict: {:?}", other)),
+        }
+    }
+
+    fn write_line(&mut self, payload: &serde_json::Value) -> Result<(), String> {
+        writeln!(self.writer, "{}", payload).map_err(|e| format!("Failed to write to relay: {}", e))?;
+        self.writer.flush().map_err(|e| format!("Failed to flush relay: {}", e))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::petri::cpn::{ArcSpec, ArcTokenPattern, Marking, Severity, Token, Transition};
+
+    /// A `RelayClient` wired to one end of a socket pair, with the other end
+    /// handed back so a test can play the external monitor.
+    fn test_client() -> (RelayClient, UnixStream) {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let read_half = ours.try_clone().unwrap();
+        let client = RelayClient { reader: BufReader::new(Box::new(read_half)), writer: Box::new(ours), next_seq: 0 };
+        (client, theirs)
+    }
+
+    /// Read one line off `peer` (the forwarded event) and reply with `reply`.
+    fn respond(mut peer: UnixStream, reply: serde_json::Value) {
+        let mut line = String::new();
+        BufReader::new(peer.try_clone().unwrap()).read_line(&mut line).unwrap();
+        writeln!(peer, "{}", reply).unwrap();
+    }
+
+    /// Test 1: a bare `"ok"` verdict string parses as [`RelayVerdict::Ok`].
+    #[test]
+    fn test_send_event_parses_ok_verdict() {
+        let (mut client, peer) = test_client();
+        let handle = std::thread::spawn(move || respond(peer, serde_json::json!({"seq": 0, "verdict": "ok"})));
+
+        let verdict = client.send_event(&PetriEvent::Yield { tid: 0 }, None, 42).unwrap();
+        handle.join().unwrap();
+        assert!(matches!(verdict, RelayVerdict::Ok));
+    }
+
+    /// Test 2: an object verdict with a `violation` message parses as
+    /// [`RelayVerdict::Violation`] carrying that message.
+    #[test]
+    fn test_send_event_parses_violation_verdict() {
+        let (mut client, peer) = test_client();
+        let handle = std::thread::spawn(move || {
+            respond(peer, serde_json::json!({"seq": 0, "verdict": {"violation": "nope"}}))
+        });
+
+        let verdict = client.send_event(&PetriEvent::Yield { tid: 0 }, None, 42).unwrap();
+        handle.join().unwrap();
+        match verdict {
+            RelayVerdict::Violation(message) => assert_eq!(message, "nope"),
+            RelayVerdict::Ok => panic!("expected a violation verdict"),
+        }
+    }
+
+    /// Test 3: a reply whose `seq` doesn't match the sent event is rejected
+    /// rather than silently accepted as the answer to a later event.
+    #[test]
+    fn test_send_event_rejects_out_of_sequence_reply() {
+        let (mut client, peer) = test_client();
+        let handle = std::thread::spawn(move || respond(peer, serde_json::json!({"seq": 99, "verdict": "ok"})));
+
+        let err = client.send_event(&PetriEvent::Yield { tid: 0 }, None, 42).unwrap_err();
+        handle.join().unwrap();
+        assert!(err.contains("out of sequence"));
+    }
+
+    /// Test 4: the handshake payload carries the net's full definition —
+    /// referenced places, each transition's arcs/guard/severity, and the
+    /// initial marking — not just transition ids, so an external monitor
+    /// can reconstruct the net and track its marking independently.
+    #[test]
+    fn test_handshake_sends_full_net_definition() {
+        let (mut client, peer) = test_client();
+
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(
+            Transition::new(
+                "acquire",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+                vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            )
+            .with_severity(Severity::Warning),
+        );
+
+        let handle = std::thread::spawn(move || {
+            let mut reader = BufReader::new(peer);
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            line
+        });
+
+        client.handshake(&engine).unwrap();
+        let line = handle.join().unwrap();
+        let payload: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        let net = &payload["handshake"];
+
+        assert_eq!(net["places"], serde_json::json!(["free", "held"]));
+        let acquire = &net["transitions"]["acquire"];
+        assert_eq!(acquire["pre"][0]["place"], "free");
+        assert_eq!(acquire["pre"][0]["variable"], "L");
+        assert_eq!(acquire["post"][0]["place"], "held");
+        assert_eq!(acquire["severity"], "warning");
+        assert_eq!(net["initial_marking"]["free"][0]["kind"], "Lock");
+        assert_eq!(net["initial_marking"]["free"][0]["value"], 1);
+    }
+}
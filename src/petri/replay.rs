@@ -0,0 +1,299 @@
+//! Offline replay and minimization of recorded NDJSON event traces.
+//!
+//! `PetriRuntime` logs each fired event to `log_path` while running under
+//! Miri, preceded by a header line describing the net's initial marking.
+//! This module re-feeds the events (skipping that header) through a fresh
+//! `CpnEngine` loaded from the same net definition, so a run can be
+//! re-checked (or turned into a regression test) without re-executing under
+//! Miri at all.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::config::PetriConfig;
+use super::diagnostic::{format_violation, PetriViolation};
+use super::event::PetriEvent;
+use super::runtime::PetriRuntime;
+
+/// A violation encountered while replaying a trace, tagged with its
+/// position in the (possibly minimized) event sequence.
+#[derive(Debug, Clone)]
+pub struct ReplayViolation {
+    pub event_index: usize,
+    pub violation: PetriViolation,
+}
+
+/// Read `trace_path` as NDJSON lines and pull out the `event` field of
+/// each, in order. The leading header line (the net's initial marking, with
+/// no `event` field) is skipped, as is any other line missing it.
+pub fn read_trace(trace_path: &Path) -> Result<Vec<PetriEvent>, String> {
+    let file = File::open(trace_path)
+        .map_err(|e| format!("Failed to open trace {}: {}", trace_path.display(), e))?;
+
+    let mut events = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read trace {} line {}: {}", trace_path.display(), line_no + 1, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|e| format!("Failed to parse trace {} line {}: {}", trace_path.display(), line_no + 1, e))?;
+        let Some(event_value) = value.get("event") else {
+            continue;
+        };
+        let event: PetriEvent = serde_json::from_value(event_value.clone())
+            .map_err(|e| format!("Failed to parse event on trace {} line {}: {}", trace_path.display(), line_no + 1, e))?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Feed `events` through a fresh net loaded from `net_path`, in order.
+/// Returns the first violation that would have aborted the live run (see
+/// `PetriRuntime::violation_aborts`); a logged-but-non-aborting violation
+/// (Warning/Info, or Error without `fail_fast`) didn't stop the original
+/// run, so it doesn't stop this replay either.
+fn run_trace(net_path: &Path, events: &[PetriEvent]) -> Result<Option<ReplayViolation>, String> {
+    let mut runtime = PetriRuntime::load(PetriConfig::new(net_path.to_path_buf()))?;
+    for (i, event) in events.iter().enumerate() {
+        if let Err(violation) = runtime.on_event(event.clone(), None) {
+            if runtime.violation_aborts(&violation) {
+                return Ok(Some(ReplayViolation { event_index: i, violation }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Replay a recorded trace against `net_path` and report the first
+/// violation, with its event index and the reconstructed marking.
+pub fn replay_trace(net_path: &Path, trace_path: &Path) -> Result<Option<ReplayViolation>, String> {
+    let events = read_trace(trace_path)?;
+    run_trace(net_path, &events)
+}
+
+/// Whether two violations are "the same" for minimization purposes: same
+/// triggering event type and the same missing/inhibiting tokens. Bit-exact
+/// equality (e.g. thread ids) would defeat minimization, since shrinking the
+/// trace is expected to change which concrete tokens are in play.
+fn same_violation(a: &PetriViolation, b: &PetriViolation) -> bool {
+    a.event.event_type_name() == b.event.event_type_name()
+        && a.reason == b.reason
+        && a.missing_tokens == b.missing_tokens
+}
+
+/// Delta-debug `events` down to the shortest contiguous-chunk-removable
+/// subsequence that still reproduces `target` against `net_path`. Standard
+/// ddmin sweep: try ever-smaller chunk sizes, restarting the sweep from the
+/// front whenever a chunk is successfully dropped.
+pub fn minimize_trace(
+    net_path: &Path,
+    events: &[PetriEvent],
+    target: &PetriViolation,
+) -> Result<Vec<PetriEvent>, String> {
+    let mut current = events.to_vec();
+    let mut chunk_size = current.len() / 2;
+
+    while chunk_size >= 1 {
+        let mut start = 0;
+        let mut shrunk_at_this_size = false;
+        while start < current.len() {
+            let end = (start + chunk_size).min(current.len());
+            let mut candidate = current.clone();
+            candidate.drain(start..end);
+
+            let reproduces = !candidate.is_empty()
+                && run_trace(net_path, &candidate)?
+                    .is_some_and(|r| same_violation(&r.violation, target));
+
+            if reproduces {
+                current = candidate;
+                shrunk_at_this_size = true;
+                // Re-try the same chunk size from the top of the shrunk
+                // trace: dropping this chunk may have made an earlier chunk
+                // (already tried and kept, at `start` < here) removable too,
+                // so resuming past it here wouldn't be 1-minimal.
+                start = 0;
+            } else {
+                start += chunk_size;
+            }
+        }
+        if !shrunk_at_this_size {
+            chunk_size /= 2;
+        }
+    }
+
+    Ok(current)
+}
+
+/// Standalone entry point: replay `trace_path` against `net_path`, minimize
+/// the trace if it reproduces a violation, and print the minimized event
+/// list plus the violation. Intended for regression tests and shareable bug
+/// reports that don't depend on the full Miri interpreter.
+pub fn run(net_path: &Path, trace_path: &Path) -> Result<(), String> {
+    let events = read_trace(trace_path)?;
+    let Some(first) = run_trace(net_path, &events)? else {
+        println!("Replay of {} events: no violation found.", events.len());
+        return Ok(());
+    };
+
+    let minimized = minimize_trace(net_path, &events, &first.violation)?;
+    println!("Minimized reproducer: {} of {} events.", minimized.len(), events.len());
+    for event in &minimized {
+        println!("  {:?}", event);
+    }
+    println!("{}", format_violation(&first.violation));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A net with one default-severity (`Error`) transition `take` (`free: L
+    /// -> held: L`, starting with one `Lock(1)` in `free`), so acquiring the
+    /// same lock twice fails the second time. Deliberately not named
+    /// `acquire` — `PetriRuntime::on_event` special-cases that exact id to
+    /// lazily reseed a missing lock token into `free`, which would mask the
+    /// violation these tests are after.
+    const TAKE_ONLY_NET: &str = r#"{
+        "transitions": {
+            "take": {
+                "pre": [{"place": "free", "variable": "L"}],
+                "post": [{"place": "held", "variable": "L"}]
+            }
+        },
+        "event_mapping": {"LockAcquire": "take"},
+        "initial_marking": {"free": [["Lock", 1]]}
+    }"#;
+
+    /// Same as `TAKE_ONLY_NET`, plus a `Warning`-severity `inc` transition
+    /// (mapped from `AtomicLoad`) that's never enabled, so it violates every
+    /// time without ever aborting.
+    const TAKE_AND_WARNING_NET: &str = r#"{
+        "transitions": {
+            "take": {
+                "pre": [{"place": "free", "variable": "L"}],
+                "post": [{"place": "held", "variable": "L"}]
+            },
+            "inc": {
+                "pre": [{"place": "counter", "variable": "loc"}],
+                "post": [],
+                "severity": "warning"
+            }
+        },
+        "event_mapping": {"LockAcquire": "take", "AtomicLoad": "inc"},
+        "initial_marking": {"free": [["Lock", 1]]}
+    }"#;
+
+    /// Write `contents` to a uniquely-named temp file and return its path.
+    fn write_net(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Test 1: the header line (no `event` field) and any other event-less
+    /// line are skipped; the rest parse into events in order.
+    #[test]
+    fn test_read_trace_skips_header_and_event_less_lines() {
+        let e1 = PetriEvent::Yield { tid: 0 };
+        let e2 = PetriEvent::LockAcquire { tid: 1, lock_id: 7 };
+        let header = serde_json::json!({"initial_marking": {}});
+        let line1 = serde_json::json!({"event": e1, "expected_marking_hash": 1});
+        let line2 = serde_json::json!({"note": "not an event, no 'event' field"});
+        let line3 = serde_json::json!({"event": e2, "expected_marking_hash": 2});
+
+        let trace_path = std::env::temp_dir().join(format!("petri_test_read_trace_{}.ndjson", std::process::id()));
+        std::fs::write(
+            &trace_path,
+            format!(
+                "{}\n\n{}\n{}\n{}\n",
+                serde_json::to_string(&header).unwrap(),
+                serde_json::to_string(&line1).unwrap(),
+                serde_json::to_string(&line2).unwrap(),
+                serde_json::to_string(&line3).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let events = read_trace(&trace_path).unwrap();
+        std::fs::remove_file(&trace_path).unwrap();
+        assert_eq!(events, vec![e1, e2]);
+    }
+
+    /// Test 2: `run_trace` stops at the first violation that would have
+    /// aborted the live run, skipping past non-aborting ones exactly as
+    /// `PetriRuntime::replay` does (see `test_replay_continues_past_non_aborting_violation`
+    /// in `runtime.rs`).
+    #[test]
+    fn test_run_trace_stops_only_on_aborting_violation() {
+        let net_path = write_net("petri_test_run_trace_abort", TAKE_AND_WARNING_NET);
+        let events = vec![
+            PetriEvent::AtomicLoad { tid: 0, loc_id: 1, ordering: "SeqCst".to_string() }, // Warning, non-aborting
+            PetriEvent::LockAcquire { tid: 0, lock_id: 1 },                               // succeeds
+            PetriEvent::AtomicLoad { tid: 0, loc_id: 1, ordering: "SeqCst".to_string() }, // Warning, non-aborting
+            PetriEvent::LockAcquire { tid: 0, lock_id: 1 },                               // Error: lock already held
+        ];
+
+        let result = run_trace(&net_path, &events).unwrap();
+        std::fs::remove_file(&net_path).unwrap();
+
+        let violation = result.expect("the second LockAcquire should abort");
+        assert_eq!(violation.event_index, 3);
+        assert_eq!(violation.violation.event, PetriEvent::LockAcquire { tid: 0, lock_id: 1 });
+    }
+
+    /// Test 3: `minimize_trace` shrinks a trace padded with unrelated `Yield`
+    /// noise down to just the two `LockAcquire` events that reproduce the
+    /// violation (acquiring an already-held lock).
+    #[test]
+    fn test_minimize_trace_shrinks_to_reproducing_events() {
+        let net_path = write_net("petri_test_minimize", TAKE_ONLY_NET);
+        let acquire = PetriEvent::LockAcquire { tid: 0, lock_id: 1 };
+        let noise = PetriEvent::Yield { tid: 0 };
+        let events =
+            vec![noise.clone(), noise.clone(), acquire.clone(), noise.clone(), noise.clone(), acquire.clone(), noise];
+
+        let first = run_trace(&net_path, &events).unwrap().expect("the repeated acquire should violate");
+        let minimized = minimize_trace(&net_path, &events, &first.violation).unwrap();
+
+        assert_eq!(minimized, vec![acquire.clone(), acquire]);
+        let reproduced = run_trace(&net_path, &minimized).unwrap().expect("minimized trace should still violate");
+        assert!(same_violation(&reproduced.violation, &first.violation));
+
+        std::fs::remove_file(&net_path).unwrap();
+    }
+
+    /// Test 4: a longer, heavier-padded trace (forcing several chunk sizes
+    /// and more than one shrink within a single chunk-size sweep) still
+    /// minimizes all the way down to the 2 reproducing events, not some
+    /// intermediate point the sweep happened to stop scanning past —
+    /// `minimize_trace` resets to the front of the trace after every
+    /// successful chunk removal, exactly as its doc comment says.
+    #[test]
+    fn test_minimize_trace_fully_minimal_across_multiple_shrinks() {
+        let net_path = write_net("petri_test_minimize_multi", TAKE_ONLY_NET);
+        let acquire = PetriEvent::LockAcquire { tid: 0, lock_id: 1 };
+        let noise = PetriEvent::Yield { tid: 0 };
+        let events = vec![
+            noise.clone(),
+            acquire.clone(),
+            noise.clone(),
+            noise.clone(),
+            acquire.clone(),
+            noise.clone(),
+            noise.clone(),
+            acquire.clone(),
+            noise,
+        ];
+
+        let first = run_trace(&net_path, &events).unwrap().expect("the repeated acquire should violate");
+        let minimized = minimize_trace(&net_path, &events, &first.violation).unwrap();
+        std::fs::remove_file(&net_path).unwrap();
+
+        assert_eq!(minimized, vec![acquire.clone(), acquire]);
+    }
+}
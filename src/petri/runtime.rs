@@ -4,11 +4,17 @@ use rustc_data_structures::fx::FxHashMap;
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufWriter, Write};
+use std::path::Path;
 
+use super::analysis::enabled_transitions;
 use super::config::PetriConfig;
-use super::cpn::{ArcSpec, ArcTokenPattern, CpnEngine, Marking, Token, Transition};
+use super::cpn::{
+    marking_to_json, ArcSpec, ArcTokenPattern, ColorValue, CpnEngine, Guard, GuardOp, GuardOperand, Marking,
+    NotEnabledReason, Severity, Token, Transition, TransitionId,
+};
 use super::diagnostic::{format_violation, PetriViolation, SpanLike};
 use super::event::PetriEvent;
+use super::relay::{RelayClient, RelayVerdict};
 
 /// JSON structure for loading net definition.
 #[derive(Debug, serde::Deserialize)]
@@ -21,12 +27,66 @@ struct PetriNetDef {
     event_mapping: FxHashMap<String, String>,
     #[serde(default)]
     initial_marking: FxHashMap<String, Vec<serde_json::Value>>,
+    /// User-declared token colors beyond the built-in `Lock`/`Loc`/`Tid`/
+    /// `Region`/`Unit` kinds, keyed by color name, e.g. `{"file": "string"}`.
+    #[serde(default)]
+    colors: FxHashMap<String, ColorKind>,
+    /// Per-event-type binding overrides: which variable name (and color) an
+    /// event's object id should be bound to, instead of the built-in
+    /// `L`/`loc` names. Keyed by `PetriEvent::event_type_name`.
+    #[serde(default)]
+    event_bindings: FxHashMap<String, EventBindingDef>,
+}
+
+/// A color's declared representation, read from the net's `colors` table.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ColorKind {
+    Int,
+    Uint,
+    String,
+    Bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct EventBindingDef {
+    variable: String,
+    color: String,
 }
 
 #[derive(Debug, serde::Deserialize)]
 struct TransitionDef {
     pre: Vec<ArcDef>,
     post: Vec<ArcDef>,
+    /// Inhibitor arcs: the transition is enabled only while the place does
+    /// *not* contain the resolved token.
+    #[serde(default)]
+    inhibit: Vec<ArcDef>,
+    /// Optional binding predicate, e.g. `{"lhs": "L", "op": "!=", "rhs": "L2"}`.
+    #[serde(default)]
+    guard: Option<GuardDef>,
+    /// Severity to report if this transition is found not enabled.
+    /// Defaults to `Severity::Error` (abort, subject to `fail_fast`).
+    #[serde(default)]
+    severity: Option<Severity>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct GuardDef {
+    lhs: String,
+    op: String,
+    rhs: String,
+}
+
+impl GuardDef {
+    fn to_guard(&self) -> Result<Guard, String> {
+        let op = match self.op.as_str() {
+            "==" | "eq" => GuardOp::Eq,
+            "!=" | "ne" => GuardOp::Ne,
+            other => return Err(format!("unknown guard operator '{}'", other)),
+        };
+        Ok(Guard::new(GuardOperand::Variable(self.lhs.clone()), op, GuardOperand::Variable(self.rhs.clone())))
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -40,23 +100,16 @@ struct ArcDef {
 #[serde(untagged)]
 enum ArcTokenDef {
     Variable { variable: String },
-    Concrete { kind: String, value: u64 },
+    Concrete { kind: String, value: serde_json::Value },
     Unit {},
 }
 
 impl ArcTokenDef {
-    fn to_arc_spec(&self, place: &str) -> ArcSpec {
+    fn to_arc_spec(&self, place: &str, colors: &FxHashMap<String, ColorKind>) -> ArcSpec {
         let token = match self {
             ArcTokenDef::Variable { variable } => ArcTokenPattern::Variable(variable.clone()),
             ArcTokenDef::Concrete { kind, value } => {
-                let t = match kind.as_str() {
-                    "Lock" => Token::Lock(*value),
-                    "Loc" => Token::Loc(*value),
-                    "Tid" => Token::Tid(*value as u32),
-                    "Region" => Token::Region(*value),
-                    _ => Token::Unit,
-                };
-                ArcTokenPattern::Concrete(t)
+                ArcTokenPattern::Concrete(token_from_kind_value(kind, value, colors))
             }
             ArcTokenDef::Unit {} => ArcTokenPattern::Concrete(Token::Unit),
         };
@@ -67,48 +120,176 @@ impl ArcTokenDef {
     }
 }
 
+/// Convert a JSON value to the `ColorValue` representation declared for a
+/// color, defaulting to the type's zero value on a mismatch.
+fn convert_color_value(kind: ColorKind, value: &serde_json::Value) -> ColorValue {
+    match kind {
+        ColorKind::Int => ColorValue::I64(value.as_i64().unwrap_or(0)),
+        ColorKind::Uint => ColorValue::U64(value.as_u64().unwrap_or(0)),
+        ColorKind::String => ColorValue::Str(value.as_str().unwrap_or("").to_string()),
+        ColorKind::Bool => ColorValue::Bool(value.as_bool().unwrap_or(false)),
+    }
+}
+
+/// Convert an event's raw object id to the `ColorValue` representation
+/// declared for a color, so a token built from a live event matches the
+/// same `ColorValue` variant `convert_color_value` built for the net's
+/// initial marking (see [`PetriRuntime::make_binding`]).
+fn color_value_from_id(kind: ColorKind, id: u64) -> ColorValue {
+    match kind {
+        ColorKind::Int => ColorValue::I64(id as i64),
+        ColorKind::Uint => ColorValue::U64(id),
+        ColorKind::String => ColorValue::Str(id.to_string()),
+        ColorKind::Bool => ColorValue::Bool(id != 0),
+    }
+}
+
+/// Build a `Token` from a `kind`/`value` pair: one of the built-in kinds
+/// (`Lock`/`Loc`/`Tid`/`Region`/`Unit`), or, if `kind` instead names a color
+/// declared in the net's `colors` table, a `Token::Colored` converted to
+/// that color's declared type.
+fn token_from_kind_value(kind: &str, value: &serde_json::Value, colors: &FxHashMap<String, ColorKind>) -> Token {
+    if let Some(&color_kind) = colors.get(kind) {
+        return Token::Colored { color: kind.to_string(), value: convert_color_value(color_kind, value) };
+    }
+    let n = value.as_u64().unwrap_or(0);
+    match kind {
+        "Lock" => Token::Lock(n),
+        "Loc" => Token::Loc(n),
+        "Tid" => Token::Tid(n as u32),
+        "Region" => Token::Region(n),
+        _ => Token::Unit,
+    }
+}
+
 /// Parse a token from JSON: ["Lock", 1] or {"kind":"Lock","value":1}.
-fn parse_initial_token(v: &serde_json::Value) -> Token {
+fn parse_initial_token(v: &serde_json::Value, colors: &FxHashMap<String, ColorKind>) -> Token {
     if let Some(arr) = v.as_array() {
         if arr.len() >= 2 {
             let kind = arr[0].as_str().unwrap_or("");
-            let value = arr[1].as_u64().unwrap_or(0);
-            return match kind {
-                "Lock" => Token::Lock(value),
-                "Loc" => Token::Loc(value),
-                "Tid" => Token::Tid(value as u32),
-                "Region" => Token::Region(value),
-                _ => Token::Unit,
-            };
+            return token_from_kind_value(kind, &arr[1], colors);
         }
     }
     if let Some(obj) = v.as_object() {
         let kind = obj.get("kind").and_then(|v| v.as_str()).unwrap_or("");
-        let value = obj.get("value").and_then(|v| v.as_u64()).unwrap_or(0);
-        return match kind {
-            "Lock" => Token::Lock(value),
-            "Loc" => Token::Loc(value),
-            "Tid" => Token::Tid(value as u32),
-            "Region" => Token::Region(value),
-            _ => Token::Unit,
-        };
+        let value = obj.get("value").cloned().unwrap_or(serde_json::Value::Null);
+        return token_from_kind_value(kind, &value, colors);
     }
     Token::Unit
 }
 
+/// One recorded event in a trace written to `log_path`: the event itself,
+/// the marking hash expected right after it fires, and the span it was
+/// recorded with (if any). Read back by [`PetriRuntime::replay`].
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct TraceEntry {
+    event: PetriEvent,
+    expected_marking_hash: u64,
+    #[serde(default)]
+    span: Option<SpanLike>,
+}
+
+/// The first line of a trace file: the net's initial marking, recorded for
+/// the reader's information (replay rewinds via `PetriRuntime::reset`, which
+/// already knows the net's initial marking, rather than re-parsing this).
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct TraceHeader {
+    initial_marking: FxHashMap<String, Vec<serde_json::Value>>,
+}
+
+/// Outcome of replaying a recorded trace against an already-loaded
+/// [`PetriRuntime`].
+#[derive(Debug)]
+pub enum ReplayOutcome {
+    /// Every recorded event reproduced its expected marking hash.
+    Ok,
+    /// An event's resulting marking hash did not match what was recorded.
+    Divergence { event_index: usize, expected_hash: u64, actual_hash: u64 },
+    /// An event was not enabled on replay, exactly as it would be live.
+    Violation { event_index: usize, violation: PetriViolation },
+}
+
+/// Coverage recorded for one marking observed during this runtime's
+/// lifetime, for [`PetriRuntime::analysis_report`]. Only populated when
+/// `config.collect_coverage` or `config.fail_on_analysis_issues` is set;
+/// see [`PetriRuntime::coverage_enabled`].
+struct ObservedMarking {
+    marking: Marking,
+    /// Transition ids found enabled under this marking (by
+    /// `analysis::enabled_transitions`), regardless of whether they fired.
+    enabled: HashSet<TransitionId>,
+    /// Transition ids actually fired from this marking.
+    fired: HashSet<TransitionId>,
+}
+
+/// Result of [`PetriRuntime::analysis_report`]: coverage gaps found across
+/// every marking observed so far.
+#[derive(Debug)]
+pub struct AnalysisReport {
+    /// Declared transitions that were never enabled in any observed marking.
+    pub dead_transitions: Vec<TransitionId>,
+    /// Observed markings (other than `config.accepting_markings`) with no
+    /// enabled transition: the net got stuck.
+    pub deadlocks: Vec<Marking>,
+    /// Transitions that were enabled in some observed marking but never
+    /// actually fired from one: reachable but unexercised.
+    pub enabled_but_never_fired: Vec<TransitionId>,
+}
+
 /// Runtime state for the Petri net monitor.
 pub struct PetriRuntime {
     engine: CpnEngine,
     config: PetriConfig,
     event_mapping: FxHashMap<String, String>,
+    /// Per-event-type (variable, color, color kind) binding overrides, keyed
+    /// by `PetriEvent::event_type_name`. The color kind is resolved once at
+    /// load time from the net's `colors` table, so [`make_binding`](Self::make_binding)
+    /// can build a token that matches the variant `convert_color_value`
+    /// would have produced for the same color.
+    event_bindings: FxHashMap<String, (String, String, ColorKind)>,
     initial_marking: Marking,
     seen_markings: HashSet<u64>,
     log_file: Option<BufWriter<File>>,
+    /// Connected external relay monitor, if `config.relay_path`/`relay_fd`
+    /// is set and the connection succeeded. See `petri::relay`.
+    relay: Option<RelayClient>,
+    /// Coverage over every marking observed so far, keyed by marking hash.
+    /// See [`analysis_report`](Self::analysis_report).
+    observed: FxHashMap<u64, ObservedMarking>,
 }
 
 impl PetriRuntime {
-    /// Load runtime from config file.
+    /// Load runtime from config file. The net definition format is chosen by
+    /// `config.config_path`'s extension: `.petri` for the textual DSL
+    /// (`cpn::dsl`), anything else for JSON.
     pub fn load(config: PetriConfig) -> Result<Self, String> {
+        let is_dsl = config
+            .config_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("petri"));
+        if is_dsl { Self::load_dsl(config) } else { Self::load_json(config) }
+    }
+
+    /// Load a net definition written in the textual DSL (see `cpn::dsl`).
+    fn load_dsl(config: PetriConfig) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(&config.config_path)
+            .map_err(|e| format!("Failed to read Petri config {}: {}", config.config_path.display(), e))?;
+        let file = config.config_path.display().to_string();
+        let net = super::dsl::parse(&contents, &file).map_err(|e| e.to_string())?;
+
+        let mut engine = CpnEngine::new();
+        for transition in net.transitions {
+            engine.add_transition(transition);
+        }
+        let initial_marking = net.initial_marking;
+        engine.set_initial_marking(initial_marking.clone());
+
+        Self::finish_load(config, engine, initial_marking, FxHashMap::default(), FxHashMap::default())
+    }
+
+    /// Load a net definition from the original JSON format.
+    fn load_json(config: PetriConfig) -> Result<Self, String> {
         let contents = std::fs::read_to_string(&config.config_path)
             .map_err(|e| format!("Failed to read Petri config {}: {}", config.config_path.display(), e))?;
         let def: PetriNetDef = serde_json::from_str(&contents)
@@ -122,46 +303,110 @@ impl PetriRuntime {
             let pre: Vec<ArcSpec> = tdef
                 .pre
                 .iter()
-                .map(|a| a.token.to_arc_spec(&a.place))
+                .map(|a| a.token.to_arc_spec(&a.place, &def.colors))
                 .collect();
             let post: Vec<ArcSpec> = tdef
                 .post
                 .iter()
-                .map(|a| a.token.to_arc_spec(&a.place))
+                .map(|a| a.token.to_arc_spec(&a.place, &def.colors))
                 .collect();
-            engine.add_transition(Transition {
-                id: tid.clone(),
-                pre,
-                post,
-            });
+            let inhibit: Vec<ArcSpec> = tdef
+                .inhibit
+                .iter()
+                .map(|a| a.token.to_arc_spec(&a.place, &def.colors))
+                .collect();
+
+            let mut transition = Transition::new(tid.clone(), pre, post)
+                .with_severity(tdef.severity.unwrap_or_default())
+                .with_inhibit(inhibit);
+            if let Some(guard_def) = &tdef.guard {
+                let guard = guard_def
+                    .to_guard()
+                    .map_err(|e| format!("Failed to parse guard for transition '{tid}': {e}"))?;
+                transition = transition.with_guard(guard);
+            }
+            engine.add_transition(transition);
         }
 
         let mut marking = Marking::new();
         for (place, tokens) in &def.initial_marking {
             for v in tokens {
-                let token = parse_initial_token(v);
+                let token = parse_initial_token(v, &def.colors);
                 marking.get_or_insert(place).add(token, 1);
             }
         }
         let initial_marking = marking.clone();
         engine.set_initial_marking(marking);
 
-        let log_file = config.log_path.as_ref().and_then(|p| {
+        let event_bindings = def
+            .event_bindings
+            .into_iter()
+            .map(|(event_type, binding_def)| {
+                let kind = def.colors.get(&binding_def.color).copied().unwrap_or(ColorKind::Uint);
+                (event_type, (binding_def.variable, binding_def.color, kind))
+            })
+            .collect();
+
+        Self::finish_load(config, engine, initial_marking, event_mapping, event_bindings)
+    }
+
+    fn finish_load(
+        config: PetriConfig,
+        engine: CpnEngine,
+        initial_marking: Marking,
+        event_mapping: FxHashMap<String, String>,
+        event_bindings: FxHashMap<String, (String, String, ColorKind)>,
+    ) -> Result<Self, String> {
+        let mut log_file = config.log_path.as_ref().and_then(|p| {
             File::create(p)
                 .ok()
                 .map(|f| BufWriter::new(f))
         });
 
+        if let Some(ref mut w) = log_file {
+            let header = TraceHeader { initial_marking: marking_to_json(&initial_marking) };
+            let _ = writeln!(w, "{}", serde_json::to_string(&header).unwrap_or_default());
+            let _ = w.flush();
+        }
+
+        let relay = RelayClient::connect(&config, &engine);
+
         Ok(Self {
             engine,
             config,
             event_mapping,
+            event_bindings,
             initial_marking,
             seen_markings: HashSet::new(),
             log_file,
+            relay,
+            observed: FxHashMap::default(),
         })
     }
 
+    /// Whether `observed` should be populated: coverage bookkeeping costs a
+    /// `Marking` clone and a full `candidate_bindings` scan of every
+    /// transition on every event, so it's opt-in rather than paid by every
+    /// monitor user regardless of whether `analysis_report` is ever called.
+    fn coverage_enabled(&self) -> bool {
+        self.config.collect_coverage || self.config.fail_on_analysis_issues
+    }
+
+    /// Record `marking` in the coverage map used by [`analysis_report`](Self::analysis_report),
+    /// merging in the set of transitions enabled there. Returns the
+    /// marking's hash, so callers can also mark which transition fired from it.
+    fn note_marking(&mut self, marking: &Marking) -> u64 {
+        let hash = marking.hash();
+        let enabled = enabled_transitions(&self.engine, marking);
+        let entry = self.observed.entry(hash).or_insert_with(|| ObservedMarking {
+            marking: marking.clone(),
+            enabled: HashSet::new(),
+            fired: HashSet::new(),
+        });
+        entry.enabled.extend(enabled);
+        hash
+    }
+
     /// Process an event. Returns Err(PetriViolation) if transition is not enabled.
     pub fn on_event(
         &mut self,
@@ -190,9 +435,30 @@ impl PetriRuntime {
                 }
             }
         }
+        let pre_hash = self.coverage_enabled().then(|| {
+            let pre_marking = self.engine.marking().clone();
+            self.note_marking(&pre_marking)
+        });
+
         let result = self.engine.fire(&transition_id, &binding);
 
         if let Err(ref not_enabled) = result {
+            // An event-type override (if configured) wins over the firing
+            // transition's own severity, so a net's baseline severities can
+            // be tuned per run without editing the net definition.
+            let severity = self
+                .config
+                .event_severity
+                .get(e.event_type_name())
+                .copied()
+                .or_else(|| self.engine.transitions.get(&transition_id).map(|t| t.severity))
+                .unwrap_or_default();
+            // Allow is suppressed entirely (never logged); Warning/Info (and
+            // a non-aborting Error) still belong in the replay trace, same
+            // as a successful fire, so `replay` sees the run as it happened.
+            if severity != Severity::Allow {
+                self.append_trace(e.clone(), span.clone());
+            }
             let violation = PetriViolation {
                 event: e.clone(),
                 tid: e.tid(),
@@ -200,10 +466,56 @@ impl PetriRuntime {
                 span,
                 missing_tokens: not_enabled.missing.clone(),
                 current_marking: self.engine.marking().clone(),
+                severity,
+                reason: not_enabled.reason,
+                external_message: None,
             };
             return Err(violation);
         }
 
+        if let Some(pre_hash) = pre_hash {
+            if let Some(entry) = self.observed.get_mut(&pre_hash) {
+                entry.fired.insert(transition_id.clone());
+            }
+            let post_marking = self.engine.marking().clone();
+            self.note_marking(&post_marking);
+        }
+
+        if let Some(relay) = self.relay.as_mut() {
+            match relay.send_event(&e, span.as_ref(), self.engine.marking_hash()) {
+                Ok(RelayVerdict::Ok) => {}
+                Ok(RelayVerdict::Violation(message)) => {
+                    let severity = self
+                        .config
+                        .event_severity
+                        .get(e.event_type_name())
+                        .copied()
+                        .or_else(|| self.engine.transitions.get(&transition_id).map(|t| t.severity))
+                        .unwrap_or_default();
+                    // The local fire already happened and mutated the
+                    // marking; the relay veto doesn't roll that back. Unlike
+                    // the `NotEnabled` branch above (a true no-op, so an
+                    // `Allow`-severity miss is fine to drop), this event
+                    // belongs in the trace regardless of severity, or
+                    // `replay` would never re-apply a mutation that really
+                    // happened.
+                    self.append_trace(e.clone(), span.clone());
+                    return Err(PetriViolation {
+                        event: e.clone(),
+                        tid: e.tid(),
+                        object_id: e.object_id(),
+                        span,
+                        missing_tokens: vec![],
+                        current_marking: self.engine.marking().clone(),
+                        severity,
+                        reason: NotEnabledReason::External,
+                        external_message: Some(message),
+                    });
+                }
+                Err(relay_err) => eprintln!("[Petri] relay error: {}", relay_err),
+            }
+        }
+
         if self.config.print_marking_on_each_event {
             eprintln!(
                 "[Petri] After {:?}: marking hash = {}",
@@ -212,19 +524,79 @@ impl PetriRuntime {
             );
         }
 
+        self.append_trace(e, span);
+
+        Ok(())
+    }
+
+    /// Append one NDJSON entry to `log_path` (if configured) recording
+    /// `event` against the current marking hash. Shared by a successful
+    /// fire and a logged-but-not-aborting violation, so `replay` sees the
+    /// run exactly as it happened.
+    fn append_trace(&mut self, event: PetriEvent, span: Option<SpanLike>) {
         if let Some(ref mut w) = self.log_file {
-            let _ = writeln!(
-                w,
-                "{}",
-                serde_json::json!({
-                    "event": e,
-                    "marking_hash": self.engine.marking_hash()
-                })
-            );
+            let entry = TraceEntry { event, expected_marking_hash: self.engine.marking_hash(), span };
+            let _ = writeln!(w, "{}", serde_json::to_string(&entry).unwrap_or_default());
             let _ = w.flush();
         }
+    }
 
-        Ok(())
+    /// Whether `violation` would have aborted the live run: only an `Error`
+    /// severity does, and only when `fail_fast` is set (see
+    /// `hooks::emit_petri_event`). A non-aborting violation didn't stop the
+    /// original run, so offline replay (here and in `petri::replay`)
+    /// shouldn't stop on it either.
+    pub fn violation_aborts(&self, violation: &PetriViolation) -> bool {
+        violation.severity == Severity::Error && self.fail_fast()
+    }
+
+    /// Replay a recorded trace (as written to `log_path`) against this
+    /// already-loaded runtime: rewind to the net's initial marking, then
+    /// feed each recorded event through [`PetriRuntime::on_event`] in order,
+    /// comparing the resulting marking hash against what was recorded. A
+    /// logged violation that wouldn't have aborted the original run (see
+    /// [`violation_aborts`](Self::violation_aborts)) doesn't stop replay
+    /// either, but the hash comparison only skips for violations that are
+    /// true marking no-ops. A `NotEnabledReason::External` (relay veto)
+    /// fires locally *before* the relay is asked, so the marking already
+    /// changed even though the transition is reported not enabled — that
+    /// case still gets the hash check. This reproduces a run offline,
+    /// independent of Miri, for regression tests and shareable bug reports.
+    pub fn replay(&mut self, path: &Path) -> Result<ReplayOutcome, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trace {}: {}", path.display(), e))?;
+        let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+
+        let header = lines
+            .next()
+            .ok_or_else(|| format!("Trace {} is empty", path.display()))?;
+        let _header: TraceHeader = serde_json::from_str(header)
+            .map_err(|e| format!("Failed to parse trace header: {}", e))?;
+
+        self.reset();
+
+        for (i, line) in lines.enumerate() {
+            let entry: TraceEntry = serde_json::from_str(line)
+                .map_err(|e| format!("Failed to parse trace entry {}: {}", i + 1, e))?;
+            match self.on_event(entry.event, entry.span) {
+                Err(violation) if self.violation_aborts(&violation) => {
+                    return Ok(ReplayOutcome::Violation { event_index: i, violation });
+                }
+                Err(violation) if violation.reason != NotEnabledReason::External => continue,
+                Err(_) | Ok(()) => {
+                    let actual_hash = self.engine.marking_hash();
+                    if actual_hash != entry.expected_marking_hash {
+                        return Ok(ReplayOutcome::Divergence {
+                            event_index: i,
+                            expected_hash: entry.expected_marking_hash,
+                            actual_hash,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(ReplayOutcome::Ok)
     }
 
     fn get_transition_for_event(&self, e: &PetriEvent) -> Option<String> {
@@ -239,9 +611,24 @@ impl PetriRuntime {
             })
     }
 
+    /// Bind an event's thread id (always `tid`) and object id, if any, to
+    /// variables a transition's arcs can reference. The object id's variable
+    /// name and color default to the built-in `L`/`Lock` (locks) or
+    /// `loc`/`Loc` (atomics) names; a net's `event_bindings` table can
+    /// override both per event type, so user-defined colors can be routed
+    /// without touching this match.
     fn make_binding(&self, e: &PetriEvent) -> FxHashMap<String, Token> {
         let mut binding = FxHashMap::default();
         binding.insert("tid".to_string(), Token::Tid(e.tid()));
+
+        if let Some((variable, color, kind)) = self.event_bindings.get(e.event_type_name()) {
+            if let Some(id) = e.object_id() {
+                let value = color_value_from_id(*kind, id);
+                binding.insert(variable.clone(), Token::Colored { color: color.clone(), value });
+            }
+            return binding;
+        }
+
         match e {
             PetriEvent::LockAcquire { lock_id, .. } | PetriEvent::LockRelease { lock_id, .. } => {
                 binding.insert("L".to_string(), Token::Lock(*lock_id));
@@ -286,4 +673,508 @@ impl PetriRuntime {
     pub fn config(&self) -> &PetriConfig {
         &self.config
     }
+
+    /// Coverage/liveness analysis over every marking observed so far
+    /// (accumulated across `reset()`s, so it covers multiple GenMC runs):
+    /// declared transitions never seen enabled, and observed markings with
+    /// no enabled transition that aren't in `config.accepting_markings`.
+    pub fn analysis_report(&self) -> AnalysisReport {
+        let mut seen_enabled: HashSet<&TransitionId> = HashSet::new();
+        let mut seen_fired: HashSet<&TransitionId> = HashSet::new();
+        for observed in self.observed.values() {
+            seen_enabled.extend(observed.enabled.iter());
+            seen_fired.extend(observed.fired.iter());
+        }
+        let dead_transitions: Vec<TransitionId> =
+            self.engine.transitions.keys().filter(|t| !seen_enabled.contains(t)).cloned().collect();
+        let enabled_but_never_fired: Vec<TransitionId> =
+            seen_enabled.iter().filter(|t| !seen_fired.contains(**t)).map(|t| (*t).clone()).collect();
+
+        let accepting_hashes: HashSet<u64> =
+            self.config.accepting_markings.iter().map(|m| m.hash()).collect();
+        let deadlocks: Vec<Marking> = self
+            .observed
+            .values()
+            .filter(|o| o.enabled.is_empty() && !accepting_hashes.contains(&o.marking.hash()))
+            .map(|o| o.marking.clone())
+            .collect();
+
+        AnalysisReport { dead_transitions, deadlocks, enabled_but_never_fired }
+    }
+
+    /// Run [`analysis_report`](Self::analysis_report) and, if
+    /// `config.fail_on_analysis_issues` is set, turn a non-empty result into
+    /// an `Err` describing the gaps found.
+    pub fn check_analysis(&self) -> Result<(), String> {
+        if !self.config.fail_on_analysis_issues {
+            return Ok(());
+        }
+        let report = self.analysis_report();
+        if report.dead_transitions.is_empty() && report.deadlocks.is_empty() {
+            return Ok(());
+        }
+        let mut msg = String::from("Petri net coverage analysis found issues:\n");
+        if !report.dead_transitions.is_empty() {
+            msg.push_str(&format!("  Dead transitions (never enabled): {}\n", report.dead_transitions.join(", ")));
+        }
+        for marking in &report.deadlocks {
+            msg.push_str(&format!("  Deadlock marking: {:?}\n", marking));
+        }
+        Err(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Build a `PetriRuntime` directly from its fields, skipping
+    /// `PetriRuntime::load`'s file I/O: tests in this module only need the
+    /// in-memory state it would have produced.
+    fn test_runtime(
+        engine: CpnEngine,
+        event_mapping: FxHashMap<String, String>,
+        event_bindings: FxHashMap<String, (String, String, ColorKind)>,
+    ) -> PetriRuntime {
+        let initial_marking = engine.marking().clone();
+        PetriRuntime {
+            engine,
+            config: PetriConfig::new(PathBuf::new()),
+            event_mapping,
+            event_bindings,
+            initial_marking,
+            seen_markings: HashSet::new(),
+            log_file: None,
+            relay: None,
+            observed: FxHashMap::default(),
+        }
+    }
+
+    /// Test 1: `make_binding` converts an event's object id to the
+    /// `ColorValue` variant the declared color kind calls for, not always
+    /// `U64` — otherwise it can never equal a same-valued token the marking
+    /// was seeded with via a non-`uint` color.
+    #[test]
+    fn test_make_binding_honors_declared_color_kind() {
+        let mut event_bindings = FxHashMap::default();
+        event_bindings.insert("LockAcquire".to_string(), ("f".to_string(), "file".to_string(), ColorKind::Int));
+        let runtime = test_runtime(CpnEngine::new(), FxHashMap::default(), event_bindings);
+
+        let binding = runtime.make_binding(&PetriEvent::LockAcquire { tid: 0, lock_id: 7 });
+        assert_eq!(
+            binding.get("f"),
+            Some(&Token::Colored { color: "file".to_string(), value: ColorValue::I64(7) })
+        );
+    }
+
+    /// Test 2: a Warning-severity violation is still appended to the NDJSON
+    /// `log_path`, not just reported to stderr — `on_event` used to return
+    /// before the log-write block was ever reached on a failed fire.
+    #[test]
+    fn test_warning_violation_is_logged_to_ndjson() {
+        let mut engine = CpnEngine::new();
+        engine.add_transition(
+            Transition::new(
+                "inc",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("loc".to_string()) }],
+                vec![],
+            )
+            .with_severity(Severity::Warning),
+        );
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("AtomicLoad".to_string(), "inc".to_string());
+
+        let log_path = std::env::temp_dir().join(format!("petri_test_warning_log_{}.ndjson", std::process::id()));
+        let log_file = Some(BufWriter::new(File::create(&log_path).unwrap()));
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+        runtime.log_file = log_file;
+
+        let err = runtime
+            .on_event(PetriEvent::AtomicLoad { tid: 0, loc_id: 1, ordering: "SeqCst".to_string() }, None)
+            .unwrap_err();
+        assert_eq!(err.severity, Severity::Warning);
+
+        let contents = std::fs::read_to_string(&log_path).unwrap();
+        std::fs::remove_file(&log_path).unwrap();
+        assert!(contents.contains("AtomicLoad"));
+    }
+
+    /// Test 3: a transition gated on a non-`uint` colored token fires
+    /// end-to-end once `make_binding` binds by the declared color kind.
+    #[test]
+    fn test_non_uint_colored_token_fires() {
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Colored { color: "file".to_string(), value: ColorValue::I64(7) }, 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("f".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("f".to_string()) }],
+        ));
+
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut event_bindings = FxHashMap::default();
+        event_bindings.insert("LockAcquire".to_string(), ("f".to_string(), "file".to_string(), ColorKind::Int));
+        let mut runtime = test_runtime(engine, event_mapping, event_bindings);
+
+        runtime.on_event(PetriEvent::LockAcquire { tid: 0, lock_id: 7 }, None).unwrap();
+        assert!(runtime.engine.marking().get("free").map_or(true, |m| m.is_empty()));
+        assert_eq!(
+            runtime.engine.marking().get("held").unwrap().count(&Token::Colored {
+                color: "file".to_string(),
+                value: ColorValue::I64(7)
+            }),
+            1
+        );
+    }
+
+    /// Test 4: `analysis_report` finds a transition permanently blocked by
+    /// an inhibitor arc as dead, and reports the resulting stuck marking as
+    /// a deadlock — both only detectable once offline enabling-checks honor
+    /// `inhibit` the same way the live engine does.
+    #[test]
+    fn test_analysis_report_dead_transition_and_deadlock() {
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(42), 1);
+        init.add_token("writer", Token::Tid(1), 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        engine.add_transition(
+            Transition::new(
+                "blocked",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+                vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            )
+            .with_inhibit(vec![ArcSpec {
+                place: "writer".to_string(),
+                token: ArcTokenPattern::Concrete(Token::Tid(1)),
+            }]),
+        );
+
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+        runtime.config.collect_coverage = true;
+
+        runtime.on_event(PetriEvent::LockAcquire { tid: 0, lock_id: 42 }, None).unwrap();
+
+        let report = runtime.analysis_report();
+        assert_eq!(report.dead_transitions, vec!["blocked".to_string()]);
+        assert_eq!(report.deadlocks.len(), 1);
+        assert_eq!(report.deadlocks[0].get("held").unwrap().count(&Token::Lock(42)), 1);
+        assert_eq!(report.enabled_but_never_fired, Vec::<String>::new());
+    }
+
+    /// Test 5: `config.event_severity` overrides the firing transition's own
+    /// `severity` when both apply to the same event type.
+    #[test]
+    fn test_event_severity_override_wins_over_transition_severity() {
+        let mut engine = CpnEngine::new();
+        engine.add_transition(
+            Transition::new(
+                "inc",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("loc".to_string()) }],
+                vec![],
+            )
+            .with_severity(Severity::Error),
+        );
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("AtomicLoad".to_string(), "inc".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+        runtime.config.event_severity.insert("AtomicLoad".to_string(), Severity::Info);
+
+        let err = runtime
+            .on_event(PetriEvent::AtomicLoad { tid: 0, loc_id: 1, ordering: "SeqCst".to_string() }, None)
+            .unwrap_err();
+        assert_eq!(err.severity, Severity::Info);
+    }
+
+    /// Test 6: `replay` reports a [`ReplayOutcome::Divergence`] when a
+    /// recorded event's expected marking hash doesn't match what replaying
+    /// it actually produces.
+    #[test]
+    fn test_replay_detects_divergence() {
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        let mut engine = CpnEngine::new();
+        engine.set_initial_marking(init.clone());
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+
+        let trace_path =
+            std::env::temp_dir().join(format!("petri_test_replay_divergence_{}.ndjson", std::process::id()));
+        let header = TraceHeader { initial_marking: marking_to_json(&init) };
+        let entry = TraceEntry {
+            event: PetriEvent::LockAcquire { tid: 0, lock_id: 1 },
+            expected_marking_hash: 0, // deliberately wrong
+            span: None,
+        };
+        std::fs::write(
+            &trace_path,
+            format!(
+                "{}\n{}\n",
+                serde_json::to_string(&header).unwrap(),
+                serde_json::to_string(&entry).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let outcome = runtime.replay(&trace_path).unwrap();
+        std::fs::remove_file(&trace_path).unwrap();
+        match outcome {
+            ReplayOutcome::Divergence { event_index, expected_hash, .. } => {
+                assert_eq!(event_index, 0);
+                assert_eq!(expected_hash, 0);
+            }
+            other => panic!("expected a divergence, got {:?}", other),
+        }
+    }
+
+    /// Test 7: a logged Warning violation doesn't stop `replay`, since it
+    /// didn't stop the original live run either — only an `Error` violation
+    /// under `fail_fast` does (see `violation_aborts`).
+    #[test]
+    fn test_replay_continues_past_non_aborting_violation() {
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        let mut engine = CpnEngine::new();
+        engine.set_initial_marking(init.clone());
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        engine.add_transition(
+            Transition::new(
+                "inc",
+                vec![ArcSpec { place: "counter".to_string(), token: ArcTokenPattern::Variable("loc".to_string()) }],
+                vec![],
+            )
+            .with_severity(Severity::Warning),
+        );
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        event_mapping.insert("AtomicLoad".to_string(), "inc".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+
+        let mut post = Marking::new();
+        post.add_token("held", Token::Lock(1), 1);
+        let expected_hash = post.hash();
+
+        let header = TraceHeader { initial_marking: marking_to_json(&init) };
+        let warning_entry = TraceEntry {
+            event: PetriEvent::AtomicLoad { tid: 0, loc_id: 1, ordering: "SeqCst".to_string() },
+            expected_marking_hash: 0,
+            span: None,
+        };
+        let ok_entry = TraceEntry {
+            event: PetriEvent::LockAcquire { tid: 0, lock_id: 1 },
+            expected_marking_hash: expected_hash,
+            span: None,
+        };
+        let trace_path =
+            std::env::temp_dir().join(format!("petri_test_replay_skips_warning_{}.ndjson", std::process::id()));
+        std::fs::write(
+            &trace_path,
+            format!(
+                "{}\n{}\n{}\n",
+                serde_json::to_string(&header).unwrap(),
+                serde_json::to_string(&warning_entry).unwrap(),
+                serde_json::to_string(&ok_entry).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let outcome = runtime.replay(&trace_path).unwrap();
+        std::fs::remove_file(&trace_path).unwrap();
+        assert!(matches!(outcome, ReplayOutcome::Ok));
+    }
+
+    /// Test 8: a relay-vetoed event is a non-aborting violation, but unlike
+    /// one raised by the local engine itself, the local fire already
+    /// mutated the marking before the relay was asked — so `replay` must
+    /// still compare the marking hash for it, not just skip ahead as it
+    /// does for a true no-op violation (Test 7).
+    #[cfg(unix)]
+    #[test]
+    fn test_replay_checks_hash_after_relay_veto() {
+        use std::io::{BufRead, BufReader, Write as _};
+        use std::os::unix::io::IntoRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        let mut engine = CpnEngine::new();
+        engine.set_initial_marking(init.clone());
+        engine.add_transition(
+            Transition::new(
+                "acquire",
+                vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+                vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            )
+            .with_severity(Severity::Warning),
+        );
+
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        let fd = ours.into_raw_fd();
+        let monitor = std::thread::spawn(move || {
+            let mut reader = BufReader::new(theirs.try_clone().unwrap());
+            let mut peer = theirs;
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap(); // handshake
+            line.clear();
+            reader.read_line(&mut line).unwrap(); // forwarded "acquire" event
+            writeln!(peer, "{}", serde_json::json!({"seq": 0, "verdict": {"violation": "vetoed"}})).unwrap();
+        });
+
+        let config = PetriConfig::new(PathBuf::new()).with_relay_fd(fd);
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime =
+            PetriRuntime::finish_load(config, engine, init.clone(), event_mapping, FxHashMap::default()).unwrap();
+
+        let header = TraceHeader { initial_marking: marking_to_json(&init) };
+        // Deliberately wrong: the local "acquire" fire moves the Lock(1)
+        // token from "free" to "held" before the relay vetoes it, so the
+        // real post-fire hash can never match 0.
+        let entry = TraceEntry {
+            event: PetriEvent::LockAcquire { tid: 0, lock_id: 1 },
+            expected_marking_hash: 0,
+            span: None,
+        };
+        let trace_path =
+            std::env::temp_dir().join(format!("petri_test_replay_relay_veto_{}.ndjson", std::process::id()));
+        std::fs::write(
+            &trace_path,
+            format!("{}\n{}\n", serde_json::to_string(&header).unwrap(), serde_json::to_string(&entry).unwrap()),
+        )
+        .unwrap();
+
+        let outcome = runtime.replay(&trace_path).unwrap();
+        std::fs::remove_file(&trace_path).unwrap();
+        monitor.join().unwrap();
+
+        match outcome {
+            ReplayOutcome::Divergence { event_index, .. } => assert_eq!(event_index, 0),
+            other => panic!("expected a divergence from the relay-vetoed fire, got {:?}", other),
+        }
+    }
+
+    /// Test 9: `analysis_report` finds a transition permanently blocked by a
+    /// guard as dead, and reports the resulting stuck marking as a
+    /// deadlock — the guard counterpart to Test 4's inhibitor-arc case, both
+    /// only detectable once offline enabling-checks honor `guard` and
+    /// `inhibit` the same way the live engine does.
+    #[test]
+    fn test_analysis_report_dead_transition_and_deadlock_via_guard() {
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        engine.add_transition(
+            Transition::new(
+                "swap",
+                vec![
+                    ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) },
+                    ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L2".to_string()) },
+                ],
+                vec![],
+            )
+            .with_guard(Guard::new(
+                GuardOperand::Variable("L".to_string()),
+                GuardOp::Ne,
+                GuardOperand::Variable("L2".to_string()),
+            )),
+        );
+
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+        runtime.config.collect_coverage = true;
+
+        runtime.on_event(PetriEvent::LockAcquire { tid: 0, lock_id: 1 }, None).unwrap();
+
+        let report = runtime.analysis_report();
+        assert_eq!(report.dead_transitions, vec!["swap".to_string()]);
+        assert_eq!(report.deadlocks.len(), 1);
+        assert_eq!(report.deadlocks[0].get("held").unwrap().count(&Token::Lock(1)), 1);
+    }
+
+    /// Test 10: coverage bookkeeping (`observed`) stays empty by default —
+    /// it's opt-in via `config.collect_coverage`/`fail_on_analysis_issues`,
+    /// since it costs a `Marking` clone and a full enabled-set scan on every
+    /// event and would otherwise be paid by every monitor user regardless of
+    /// whether `analysis_report` is ever called.
+    #[test]
+    fn test_coverage_not_collected_by_default() {
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+
+        runtime.on_event(PetriEvent::LockAcquire { tid: 0, lock_id: 1 }, None).unwrap();
+
+        assert!(runtime.observed.is_empty());
+    }
+
+    /// Test 11: `analysis_report` lists a transition as `enabled_but_never_fired`
+    /// when it was enabled under an observed marking but some other
+    /// transition fired from there instead — distinct from `dead_transitions`,
+    /// which only covers transitions never enabled anywhere.
+    #[test]
+    fn test_analysis_report_enabled_but_never_fired() {
+        let mut engine = CpnEngine::new();
+        let mut init = Marking::new();
+        init.add_token("free", Token::Lock(1), 1);
+        engine.set_initial_marking(init);
+        engine.add_transition(Transition::new(
+            "acquire",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "held".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+        engine.add_transition(Transition::new(
+            "release_noop",
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+            vec![ArcSpec { place: "free".to_string(), token: ArcTokenPattern::Variable("L".to_string()) }],
+        ));
+
+        let mut event_mapping = FxHashMap::default();
+        event_mapping.insert("LockAcquire".to_string(), "acquire".to_string());
+        let mut runtime = test_runtime(engine, event_mapping, FxHashMap::default());
+        runtime.config.collect_coverage = true;
+
+        runtime.on_event(PetriEvent::LockAcquire { tid: 0, lock_id: 1 }, None).unwrap();
+
+        let report = runtime.analysis_report();
+        assert!(report.dead_transitions.is_empty());
+        assert_eq!(report.enabled_but_never_fired, vec!["release_noop".to_string()]);
+    }
 }